@@ -0,0 +1,60 @@
+use std::collections::BTreeMap;
+
+use anchor_lang::{prelude::*, system_program::{Transfer, transfer}};
+
+use crate::{state::{Claim, Config}, errors::DiceError};
+
+#[derive(Accounts)]
+pub struct ClaimWinnings<'info> {
+    #[account(
+        mut,
+        constraint = player.key() != house.key() @ DiceError::PlayerIsHouse,
+        constraint = player.key() != vault.key() @ DiceError::PlayerIsVault
+    )]
+    pub player: Signer<'info>,
+    pub house: SystemAccount<'info>,
+    #[account(
+        mut,
+        seeds = [b"vault", house.key().as_ref(), config.namespace.as_slice()],
+        bump
+    )]
+    pub vault: SystemAccount<'info>,
+    #[account(
+        has_one = house,
+        seeds = [b"config", house.key().as_ref(), config.namespace.as_slice()],
+        bump = config.bump
+    )]
+    pub config: Account<'info, Config>,
+    #[account(
+        mut,
+        has_one = player,
+        seeds = [b"claim", vault.key().as_ref(), player.key().as_ref()],
+        bump = claim.bump
+    )]
+    pub claim: Account<'info, Claim>,
+    pub system_program: Program<'info, System>
+}
+
+impl<'info> ClaimWinnings<'info> {
+    pub fn claim_winnings(&mut self, bumps: &BTreeMap<String, u8>) -> Result<()> {
+        let amount = self.claim.owed;
+        require_neq!(amount, 0, DiceError::NothingToClaim);
+        self.claim.owed = 0;
+
+        let accounts = Transfer {
+            from: self.vault.to_account_info(),
+            to: self.player.to_account_info()
+        };
+
+        let seeds = [b"vault", &self.house.key().to_bytes()[..], self.config.namespace.as_slice(), &[*bumps.get("vault").ok_or(DiceError::BumpError)?]];
+        let signer_seeds = &[&seeds[..]][..];
+
+        let ctx = CpiContext::new_with_signer(
+            self.system_program.to_account_info(),
+            accounts,
+            signer_seeds
+        );
+
+        transfer(ctx, amount)
+    }
+}
@@ -0,0 +1,43 @@
+use anchor_lang::prelude::*;
+
+use crate::{state::{Config, Emergency, PayoutTable}, errors::DiceError};
+
+#[derive(Accounts)]
+pub struct ClosePayoutTable<'info> {
+    #[account(mut)]
+    pub house: Signer<'info>,
+    #[account(
+        mut,
+        has_one = house,
+        seeds = [b"config", house.key().as_ref(), config.namespace.as_slice()],
+        bump = config.bump
+    )]
+    pub config: Account<'info, Config>,
+    #[account(
+        mut,
+        close = house,
+        has_one = house,
+        seeds = [b"payouts", house.key().as_ref(), config.namespace.as_slice()],
+        bump = payout_table.bump
+    )]
+    pub payout_table: Account<'info, PayoutTable>,
+    #[account(
+        seeds = [b"emergency"],
+        bump
+    )]
+    /// CHECK: Pinned to the emergency PDA, which may not be initialized yet
+    pub emergency: UncheckedAccount<'info>
+}
+
+impl<'info> ClosePayoutTable<'info> {
+    pub fn close_payout_table(&mut self) -> Result<()> {
+        let clock = Clock::get()?;
+        require!(!Emergency::is_active_at(&self.emergency, clock.slot)?, DiceError::EmergencyActive);
+        let old = self.config.terms();
+        self.config.payout_table = false;
+        self.config.validate(None)?;
+        let change = self.config.record_change(old, &clock);
+        emit!(change);
+        Ok(())
+    }
+}
@@ -0,0 +1,48 @@
+use std::collections::BTreeMap;
+
+use anchor_lang::prelude::*;
+
+use crate::{state::{Config, Emergency, Round}, errors::DiceError};
+
+#[derive(Accounts)]
+#[instruction(round_id: u64)]
+pub struct CreateRound<'info> {
+    #[account(mut)]
+    pub house: Signer<'info>,
+    #[account(
+        has_one = house,
+        seeds = [b"config", house.key().as_ref(), config.namespace.as_slice()],
+        bump = config.bump
+    )]
+    pub config: Account<'info, Config>,
+    #[account(
+        init,
+        payer = house,
+        space = Round::LEN,
+        seeds = [b"round", house.key().as_ref(), config.namespace.as_slice(), round_id.to_le_bytes().as_ref()],
+        bump
+    )]
+    pub round: Account<'info, Round>,
+    #[account(
+        seeds = [b"emergency"],
+        bump
+    )]
+    /// CHECK: Pinned to the emergency PDA, which may not be initialized yet
+    pub emergency: UncheckedAccount<'info>,
+    pub system_program: Program<'info, System>
+}
+
+impl<'info> CreateRound<'info> {
+    pub fn create_round(&mut self, bumps: &BTreeMap<String, u8>, round_id: u64) -> Result<()> {
+        // Bets use round_id 0 to mean "not in a round"
+        require_neq!(round_id, 0, DiceError::RoundMismatch);
+        require!(!Emergency::is_active_at(&self.emergency, Clock::get()?.slot)?, DiceError::EmergencyActive);
+        self.round.house = self.house.key();
+        self.round.round_id = round_id;
+        self.round.open_bets = 0;
+        self.round.finalized = false;
+        self.round.entries = Vec::new();
+        self.round.bump = *bumps.get("round").ok_or(DiceError::BumpError)?;
+        Ok(())
+    }
+}
@@ -0,0 +1,40 @@
+use anchor_lang::prelude::*;
+
+use crate::state::{Bet, Config};
+
+#[derive(Accounts)]
+pub struct DeriveAddresses<'info> {
+    pub house: SystemAccount<'info>,
+    // Decides the namespace and which bet seed layout applies
+    #[account(
+        has_one = house,
+        seeds = [b"config", house.key().as_ref(), config.namespace.as_slice()],
+        bump = config.bump
+    )]
+    pub config: Account<'info, Config>
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+pub struct Addresses {
+    pub vault: Pubkey,
+    pub config: Pubkey,
+    pub bet: Pubkey
+}
+
+impl<'info> DeriveAddresses<'info> {
+    // Where place_bet would put a bet by `player` under `seed` right now, on
+    // this config's table
+    pub fn derive_addresses(&self, player: Pubkey, seed: u128) -> Addresses {
+        let house = self.house.key();
+        let compact = self.config.compact_seeds;
+        let namespace = self.config.namespace.as_slice();
+        let vault = Pubkey::find_program_address(&[b"vault", house.as_ref(), namespace], &crate::ID).0;
+        let config = Pubkey::find_program_address(&[b"config", house.as_ref(), namespace], &crate::ID).0;
+        let bet = Pubkey::find_program_address(&[b"bet", vault.as_ref(), Bet::prefix_for(compact, &player).as_ref(), Bet::seed_bytes_for(compact, seed).as_ref()], &crate::ID).0;
+        Addresses {
+            vault,
+            config,
+            bet
+        }
+    }
+}
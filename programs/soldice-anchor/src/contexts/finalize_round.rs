@@ -0,0 +1,35 @@
+use anchor_lang::prelude::*;
+
+use crate::{state::{Config, Emergency, Round}, errors::DiceError};
+
+#[derive(Accounts)]
+pub struct FinalizeRound<'info> {
+    // Otherwise anyone could end the round in a lull between bets
+    pub house: Signer<'info>,
+    #[account(
+        has_one = house,
+        seeds = [b"config", house.key().as_ref(), config.namespace.as_slice()],
+        bump = config.bump
+    )]
+    pub config: Account<'info, Config>,
+    #[account(
+        mut,
+        has_one = house,
+        seeds = [b"round", house.key().as_ref(), config.namespace.as_slice(), round.round_id.to_le_bytes().as_ref()],
+        bump = round.bump
+    )]
+    pub round: Account<'info, Round>,
+    #[account(
+        seeds = [b"emergency"],
+        bump
+    )]
+    /// CHECK: Pinned to the emergency PDA, which may not be initialized yet
+    pub emergency: UncheckedAccount<'info>
+}
+
+impl<'info> FinalizeRound<'info> {
+    pub fn finalize_round(&mut self) -> Result<()> {
+        require!(!Emergency::is_active_at(&self.emergency, Clock::get()?.slot)?, DiceError::EmergencyActive);
+        self.round.finalize()
+    }
+}
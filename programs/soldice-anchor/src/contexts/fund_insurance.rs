@@ -0,0 +1,41 @@
+use anchor_lang::{prelude::*, system_program::{Transfer, transfer}};
+
+use crate::state::Config;
+
+#[derive(Accounts)]
+pub struct FundInsurance<'info> {
+    #[account(mut)]
+    pub house: Signer<'info>,
+    #[account(
+        has_one = house,
+        seeds = [b"config", house.key().as_ref(), config.namespace.as_slice()],
+        bump = config.bump
+    )]
+    pub config: Account<'info, Config>,
+    // The first deposit creates the fund, so it must cover rent
+    #[account(
+        mut,
+        seeds = [b"insurance", house.key().as_ref(), config.namespace.as_slice()],
+        bump
+    )]
+    pub insurance: SystemAccount<'info>,
+    pub system_program: Program<'info, System>
+}
+
+impl<'info> FundInsurance<'info> {
+    // Allowed during an emergency: refunds of insured bets return their
+    // premium out of the fund, so the house may need to top it up
+    pub fn fund_insurance(&mut self, amount: u64) -> Result<()> {
+        let accounts = Transfer {
+            from: self.house.to_account_info(),
+            to: self.insurance.to_account_info()
+        };
+
+        let ctx = CpiContext::new(
+            self.system_program.to_account_info(),
+            accounts
+        );
+
+        transfer(ctx, amount)
+    }
+}
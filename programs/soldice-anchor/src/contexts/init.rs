@@ -1,20 +1,51 @@
+use std::collections::BTreeMap;
+
 use anchor_lang::{prelude::*, system_program::{Transfer, transfer}};
 
+use crate::{state::Config, errors::DiceError};
+
+pub const HOUSE_EDGE: u16 = 150; // 1.5% default house edge
+
 #[derive(Accounts)]
+#[instruction(amount: u64, min_bankroll: u64, namespace: Vec<u8>)]
 pub struct Initialize<'info> {
     #[account(mut)]
     pub house: Signer<'info>,
     #[account(
         mut,
-        seeds = [b"vault", house.key().as_ref()],
+        seeds = [b"vault", house.key().as_ref(), namespace.as_slice()],
         bump
     )]
     pub vault: SystemAccount<'info>,
+    #[account(
+        init,
+        payer = house,
+        space = Config::LEN,
+        seeds = [b"config", house.key().as_ref(), namespace.as_slice()],
+        bump
+    )]
+    pub config: Account<'info, Config>,
     pub system_program: Program<'info, System>
 }
 
 impl<'info> Initialize<'info> {
+    pub fn init_config(&mut self, bumps: &BTreeMap<String, u8>, min_bankroll: u64, namespace: Vec<u8>) -> Result<()> {
+        let old = self.config.terms();
+        self.config.house = self.house.key();
+        self.config.namespace = namespace;
+        self.config.min_bankroll = min_bankroll;
+        self.config.house_edge = HOUSE_EDGE;
+        self.config.bump = *bumps.get("config").ok_or(DiceError::BumpError)?;
+        self.config.validate(None)?;
+        let change = self.config.record_change(old, &Clock::get()?);
+        emit!(change);
+        Ok(())
+    }
+
     pub fn init(&mut self, amount: u64) -> Result<()> {
+        // The vault must start out holding at least the advertised bankroll
+        require!(amount >= self.config.min_bankroll, DiceError::MinimumBankroll);
+
         let accounts = Transfer {
             from: self.house.to_account_info(),
             to: self.vault.to_account_info()
@@ -27,4 +58,4 @@ impl<'info> Initialize<'info> {
 
         transfer(ctx, amount)
     }
-}
\ No newline at end of file
+}
@@ -0,0 +1,45 @@
+use std::collections::BTreeMap;
+
+use anchor_lang::prelude::*;
+
+use crate::{state::{Claim, Config}, errors::DiceError};
+
+#[derive(Accounts)]
+pub struct InitClaim<'info> {
+    #[account(
+        mut,
+        constraint = player.key() != house.key() @ DiceError::PlayerIsHouse,
+        constraint = player.key() != vault.key() @ DiceError::PlayerIsVault
+    )]
+    pub player: Signer<'info>,
+    pub house: SystemAccount<'info>,
+    #[account(
+        seeds = [b"vault", house.key().as_ref(), config.namespace.as_slice()],
+        bump
+    )]
+    pub vault: SystemAccount<'info>,
+    #[account(
+        has_one = house,
+        seeds = [b"config", house.key().as_ref(), config.namespace.as_slice()],
+        bump = config.bump
+    )]
+    pub config: Account<'info, Config>,
+    #[account(
+        init,
+        payer = player,
+        space = Claim::LEN,
+        seeds = [b"claim", vault.key().as_ref(), player.key().as_ref()],
+        bump
+    )]
+    pub claim: Account<'info, Claim>,
+    pub system_program: Program<'info, System>
+}
+
+impl<'info> InitClaim<'info> {
+    pub fn init_claim(&mut self, bumps: &BTreeMap<String, u8>) -> Result<()> {
+        self.claim.player = self.player.key();
+        self.claim.owed = 0;
+        self.claim.bump = *bumps.get("claim").ok_or(DiceError::BumpError)?;
+        Ok(())
+    }
+}
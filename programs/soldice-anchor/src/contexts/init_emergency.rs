@@ -0,0 +1,40 @@
+use std::collections::BTreeMap;
+
+use anchor_lang::prelude::*;
+
+use crate::{program::SoldiceAnchor, state::Emergency, errors::DiceError};
+
+#[derive(Accounts)]
+pub struct InitEmergency<'info> {
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    #[account(
+        constraint = program.programdata_address()? == Some(program_data.key())
+    )]
+    pub program: Program<'info, SoldiceAnchor>,
+    #[account(
+        constraint = program_data.upgrade_authority_address == Some(authority.key()) @ DiceError::NotUpgradeAuthority
+    )]
+    pub program_data: Account<'info, ProgramData>,
+    #[account(
+        init,
+        payer = authority,
+        space = Emergency::LEN,
+        seeds = [b"emergency"],
+        bump
+    )]
+    pub emergency: Account<'info, Emergency>,
+    pub system_program: Program<'info, System>
+}
+
+impl<'info> InitEmergency<'info> {
+    pub fn init_emergency(&mut self, bumps: &BTreeMap<String, u8>, timelock: u64) -> Result<()> {
+        require!(timelock >= Emergency::MIN_TIMELOCK, DiceError::InvalidTimelock);
+        self.emergency.authority = self.authority.key();
+        self.emergency.timelock = timelock;
+        self.emergency.active_from = u64::MAX;
+        self.emergency.active_until = u64::MAX;
+        self.emergency.bump = *bumps.get("emergency").ok_or(DiceError::BumpError)?;
+        Ok(())
+    }
+}
@@ -0,0 +1,45 @@
+use std::collections::BTreeMap;
+
+use anchor_lang::prelude::*;
+
+use crate::{state::{Config, PlayerNonce}, errors::DiceError};
+
+#[derive(Accounts)]
+pub struct InitNonce<'info> {
+    #[account(
+        mut,
+        constraint = player.key() != house.key() @ DiceError::PlayerIsHouse,
+        constraint = player.key() != vault.key() @ DiceError::PlayerIsVault
+    )]
+    pub player: Signer<'info>,
+    pub house: SystemAccount<'info>,
+    #[account(
+        seeds = [b"vault", house.key().as_ref(), config.namespace.as_slice()],
+        bump
+    )]
+    pub vault: SystemAccount<'info>,
+    #[account(
+        has_one = house,
+        seeds = [b"config", house.key().as_ref(), config.namespace.as_slice()],
+        bump = config.bump
+    )]
+    pub config: Account<'info, Config>,
+    #[account(
+        init,
+        payer = player,
+        space = PlayerNonce::LEN,
+        seeds = [b"nonce", vault.key().as_ref(), player.key().as_ref()],
+        bump
+    )]
+    pub nonce: Account<'info, PlayerNonce>,
+    pub system_program: Program<'info, System>
+}
+
+impl<'info> InitNonce<'info> {
+    pub fn init_nonce(&mut self, bumps: &BTreeMap<String, u8>) -> Result<()> {
+        self.nonce.player = self.player.key();
+        self.nonce.nonce = 0;
+        self.nonce.bump = *bumps.get("nonce").ok_or(DiceError::BumpError)?;
+        Ok(())
+    }
+}
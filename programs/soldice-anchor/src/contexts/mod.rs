@@ -9,3 +9,57 @@ pub use resolve_bet::*;
 
 pub mod refund_bet;
 pub use refund_bet::*;
+
+pub mod update_config;
+pub use update_config::*;
+
+pub mod derive_addresses;
+pub use derive_addresses::*;
+
+pub mod set_payout_table;
+pub use set_payout_table::*;
+
+pub mod close_payout_table;
+pub use close_payout_table::*;
+
+pub mod place_side_bet;
+pub use place_side_bet::*;
+
+pub mod refund_side_bet;
+pub use refund_side_bet::*;
+
+pub mod verify_resolver;
+pub use verify_resolver::*;
+
+pub mod create_round;
+pub use create_round::*;
+
+pub mod enter_round;
+pub use enter_round::*;
+
+pub mod finalize_round;
+pub use finalize_round::*;
+
+pub mod refund_bets;
+pub use refund_bets::*;
+
+pub mod init_nonce;
+pub use init_nonce::*;
+
+pub mod init_claim;
+pub use init_claim::*;
+
+pub mod claim_winnings;
+pub use claim_winnings::*;
+
+pub mod init_emergency;
+pub use init_emergency::*;
+
+pub mod set_emergency;
+pub use set_emergency::*;
+
+pub mod set_emergency_authority;
+pub use set_emergency_authority::*;
+
+pub mod fund_insurance;
+pub use fund_insurance::*;
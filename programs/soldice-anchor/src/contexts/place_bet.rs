@@ -2,47 +2,167 @@ use std::collections::BTreeMap;
 
 use anchor_lang::{prelude::*, system_program::{Transfer, transfer}};
 
-use crate::{state::Bet, errors::DiceError};
+use crate::{state::{Bet, Claim, Config, Emergency, PayoutTable, PlayerNonce, Round}, errors::DiceError, rng::RNG_VERSION, contexts::REFUND_TIMEOUT};
 
 #[derive(Accounts)]
 #[instruction(seed:u128)]
 pub struct PlaceBet<'info> {
-    #[account(mut)]
+    #[account(
+        mut,
+        constraint = player.key() != house.key() @ DiceError::PlayerIsHouse,
+        constraint = player.key() != vault.key() @ DiceError::PlayerIsVault
+    )]
     pub player: Signer<'info>,
-    ///CHECK: This is safe
-    pub house: UncheckedAccount<'info>,
+    pub house: SystemAccount<'info>,
     #[account(
         mut,
-        seeds = [b"vault", house.key().as_ref()],
+        seeds = [b"vault", house.key().as_ref(), config.namespace.as_slice()],
         bump
     )]
     pub vault: SystemAccount<'info>,
+    #[account(
+        seeds = [b"config", house.key().as_ref(), config.namespace.as_slice()],
+        bump = config.bump
+    )]
+    pub config: Account<'info, Config>,
     #[account(
         init,
         payer = player,
         space = Bet::LEN,
-        seeds = [b"bet", vault.key().as_ref(), seed.to_le_bytes().as_ref()],
+        seeds = [b"bet", vault.key().as_ref(), Bet::prefix_for(config.compact_seeds, &player.key()).as_ref(), Bet::seed_bytes_for(config.compact_seeds, seed).as_ref()],
         bump
     )]
     pub bet: Account<'info, Bet>,
+    #[account(
+        mut,
+        has_one = house,
+        seeds = [b"round", house.key().as_ref(), config.namespace.as_slice(), round.round_id.to_le_bytes().as_ref()],
+        bump = round.bump
+    )]
+    pub round: Option<Account<'info, Round>>,
+    #[account(
+        has_one = house,
+        seeds = [b"payouts", house.key().as_ref(), config.namespace.as_slice()],
+        bump = payout_table.bump
+    )]
+    pub payout_table: Option<Account<'info, PayoutTable>>,
+    #[account(
+        mut,
+        has_one = player,
+        seeds = [b"nonce", vault.key().as_ref(), player.key().as_ref()],
+        bump = nonce.bump
+    )]
+    pub nonce: Option<Account<'info, PlayerNonce>>,
+    // Required in claim mode, so the Claim is already there when the house
+    // resolves and the player can't decide whether it exists after seeing
+    // the roll
+    #[account(
+        has_one = player,
+        seeds = [b"claim", vault.key().as_ref(), player.key().as_ref()],
+        bump = claim.bump
+    )]
+    pub claim: Option<Account<'info, Claim>>,
+    // Insurance fund, funded by premiums and topped up by the house
+    #[account(
+        mut,
+        seeds = [b"insurance", house.key().as_ref(), config.namespace.as_slice()],
+        bump
+    )]
+    pub insurance: Option<SystemAccount<'info>>,
+    // Pays the transaction fee for the player, and is reimbursed out of the
+    // stake
+    #[account(mut)]
+    pub relayer: Option<Signer<'info>>,
+    #[account(
+        seeds = [b"emergency"],
+        bump
+    )]
+    /// CHECK: Pinned to the emergency PDA, which may not be initialized yet
+    pub emergency: UncheckedAccount<'info>,
     pub system_program: Program<'info, System>
 }
 
 impl<'info> PlaceBet<'info> {
     pub fn create_bet(&mut self, bumps: &BTreeMap<String, u8>, seed: u128, roll: u8, amount: u64) -> Result<()> {
-        self.bet.slot = Clock::get()?.slot;
+        require!(roll >= Bet::MIN_ROLL, DiceError::MinimumRoll);
+        require!(roll <= Bet::MAX_ROLL, DiceError::MaximumRoll);
+        // Don't take bets against a vault that has fallen below its bankroll
+        require!(self.vault.lamports() >= self.config.min_bankroll, DiceError::MinimumBankroll);
+        let slot = Clock::get()?.slot;
+        require!(!Emergency::is_active_at(&self.emergency, slot)?, DiceError::EmergencyActive);
+        require!(!self.config.is_paused(slot), DiceError::TableClosed);
+        if self.config.compact_seeds {
+            require!(seed <= u64::MAX as u128, DiceError::CompactSeed);
+        }
+        if self.config.strict_nonce {
+            let nonce = self.nonce.as_mut().ok_or(DiceError::NonceRequired)?;
+            require!(seed == nonce.next_seed(self.config.compact_seeds), DiceError::NonceMismatch);
+            nonce.nonce = nonce.nonce.checked_add(1).ok_or(DiceError::Overflow)?;
+        }
+        let surcharge = match &self.relayer {
+            Some(_) => (amount as u128)
+                .checked_mul(self.config.relayer_surcharge as u128).ok_or(DiceError::Overflow)?
+                .checked_div(10_000).ok_or(DiceError::Overflow)? as u64,
+            None => 0
+        };
+        // Everything below is priced on the stake net of the surcharge
+        let amount = amount.checked_sub(surcharge).ok_or(DiceError::Overflow)?;
+        self.bet.slot = slot;
         self.bet.player = self.player.key();
         self.bet.seed = seed;
         self.bet.roll = roll;
         self.bet.amount = amount;
         self.bet.bump = *bumps.get("bet").ok_or(DiceError::BumpError)?;
+        self.bet.rng_version = RNG_VERSION;
+        self.bet.house_edge = self.config.house_edge;
+        require!(self.claim.is_some() || !self.config.claim_payouts, DiceError::ClaimMismatch);
+        self.bet.claim_payout = self.config.claim_payouts;
+        self.bet.compact_seed = self.config.compact_seeds;
+        // Fixed here rather than by the house when it signs, so it can't
+        // sign the same bet with several expiries and pick the best roll
+        let expiry_slots = match self.config.expiry_slots {
+            0 => REFUND_TIMEOUT,
+            expiry_slots => expiry_slots
+        };
+        self.bet.expires_at_slot = slot.checked_add(expiry_slots).ok_or(DiceError::Overflow)?;
+        // Passing the insurance fund buys insurance
+        if self.insurance.is_some() {
+            require_neq!(self.config.insurance_premium, 0, DiceError::InsuranceDisabled);
+            self.bet.premium = (amount as u128)
+                .checked_mul(self.config.insurance_premium as u128).ok_or(DiceError::Overflow)?
+                .checked_div(10_000).ok_or(DiceError::Overflow)? as u64;
+            self.bet.insurance_refund = self.config.insurance_refund;
+        }
+        // The house can't pick whichever of the two curves suits a bet
+        require!(self.payout_table.is_some() == self.config.payout_table, DiceError::PayoutTableMismatch);
+        self.bet.multiplier = match &self.payout_table {
+            Some(payout_table) => payout_table.multiplier(roll)?,
+            None => 0
+        };
+        self.bet.round_id = match &mut self.round {
+            Some(round) => {
+                round.join(self.player.key())?;
+                round.round_id
+            },
+            None => 0
+        };
         Ok(())
     }
 
     pub fn deposit(&mut self, amount: u64) -> Result<()> {
+        if let Some(relayer) = &self.relayer {
+            self.transfer_to(relayer.to_account_info(), amount.saturating_sub(self.bet.amount))?;
+        }
+        if let Some(insurance) = &self.insurance {
+            self.transfer_to(insurance.to_account_info(), self.bet.premium)?;
+        }
+        self.transfer_to(self.vault.to_account_info(), self.bet.amount)
+    }
+
+    fn transfer_to(&self, to: AccountInfo<'info>, amount: u64) -> Result<()> {
         let accounts = Transfer {
             from: self.player.to_account_info(),
-            to: self.vault.to_account_info()
+            to
         };
 
         let ctx = CpiContext::new(
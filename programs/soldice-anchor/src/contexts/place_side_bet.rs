@@ -0,0 +1,84 @@
+use std::collections::BTreeMap;
+
+use anchor_lang::{prelude::*, system_program::{Transfer, transfer}};
+
+use crate::{state::{Bet, Config, Emergency, SideBet}, errors::DiceError};
+
+#[derive(Accounts)]
+pub struct PlaceSideBet<'info> {
+    #[account(
+        mut,
+        constraint = bettor.key() != house.key() @ DiceError::PlayerIsHouse,
+        constraint = bettor.key() != vault.key() @ DiceError::PlayerIsVault
+    )]
+    pub bettor: Signer<'info>,
+    pub house: SystemAccount<'info>,
+    #[account(
+        mut,
+        seeds = [b"vault", house.key().as_ref(), config.namespace.as_slice()],
+        bump
+    )]
+    pub vault: SystemAccount<'info>,
+    #[account(
+        seeds = [b"config", house.key().as_ref(), config.namespace.as_slice()],
+        bump = config.bump
+    )]
+    pub config: Account<'info, Config>,
+    #[account(
+        mut,
+        seeds = [b"bet", vault.key().as_ref(), bet.seed_prefix().as_ref(), bet.seed_bytes().as_ref()],
+        bump = bet.bump
+    )]
+    pub bet: Account<'info, Bet>,
+    #[account(
+        init,
+        payer = bettor,
+        space = SideBet::LEN,
+        seeds = [b"side", bet.key().as_ref(), bettor.key().as_ref()],
+        bump
+    )]
+    pub side_bet: Account<'info, SideBet>,
+    #[account(
+        seeds = [b"emergency"],
+        bump
+    )]
+    /// CHECK: Pinned to the emergency PDA, which may not be initialized yet
+    pub emergency: UncheckedAccount<'info>,
+    pub system_program: Program<'info, System>
+}
+
+impl<'info> PlaceSideBet<'info> {
+    pub fn create_side_bet(&mut self, bumps: &BTreeMap<String, u8>, amount: u64, on_win: bool) -> Result<()> {
+        require!(amount >= SideBet::MIN_AMOUNT, DiceError::MinimumBet);
+        require!(self.bet.side_bets < Bet::MAX_SIDE_BETS, DiceError::TooManySideBets);
+        require!(self.vault.lamports() >= self.config.min_bankroll, DiceError::MinimumBankroll);
+        let slot = Clock::get()?.slot;
+        require!(!Emergency::is_active_at(&self.emergency, slot)?, DiceError::EmergencyActive);
+        require!(!self.config.is_paused(slot), DiceError::TableClosed);
+        require!(!self.bet.is_expired(slot), DiceError::BetExpired);
+        require!(self.bet.side_bets_open(slot), DiceError::SideBetsClosed);
+
+        self.side_bet.bettor = self.bettor.key();
+        self.side_bet.bet = self.bet.key();
+        self.side_bet.amount = amount;
+        self.side_bet.on_win = on_win;
+        self.side_bet.bump = *bumps.get("side_bet").ok_or(DiceError::BumpError)?;
+
+        // The bet can't settle until every side bet on it settles with it
+        self.bet.side_bets = self.bet.side_bets.checked_add(1).ok_or(DiceError::Overflow)?;
+        Ok(())
+    }
+
+    pub fn deposit(&mut self, amount: u64) -> Result<()> {
+        let accounts = Transfer {
+            from: self.bettor.to_account_info(),
+            to: self.vault.to_account_info()
+        };
+
+        let ctx = CpiContext::new(
+            self.system_program.to_account_info(),
+            accounts
+        );
+        transfer(ctx, amount)
+    }
+}
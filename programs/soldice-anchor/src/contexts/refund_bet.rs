@@ -1,43 +1,131 @@
 use std::collections::BTreeMap;
 
-use anchor_lang::{prelude::*, system_program::{Transfer, transfer}};
+use anchor_lang::{prelude::*, system_program::{Transfer, transfer}, AccountsClose};
 
-use crate::{state::Bet, errors::DiceError};
+use crate::{state::{Bet, Config, Emergency, Round, SideBet}, errors::DiceError};
+
+// Expiry of bets placed while the house has none configured
+pub const REFUND_TIMEOUT: u64 = 1000;
 
 #[derive(Accounts)]
 pub struct RefundBet<'info> {
-    #[account(mut)]
+    #[account(
+        mut,
+        constraint = player.key() != house.key() @ DiceError::PlayerIsHouse,
+        constraint = player.key() != vault.key() @ DiceError::PlayerIsVault
+    )]
     pub player: Signer<'info>,
-    ///CHECK: This is safe
-    pub house: UncheckedAccount<'info>,
+    pub house: SystemAccount<'info>,
     #[account(
         mut,
-        seeds = [b"vault", house.key().as_ref()],
+        seeds = [b"vault", house.key().as_ref(), config.namespace.as_slice()],
         bump
     )]
     pub vault: SystemAccount<'info>,
+    #[account(
+        seeds = [b"config", house.key().as_ref(), config.namespace.as_slice()],
+        bump = config.bump
+    )]
+    pub config: Account<'info, Config>,
     #[account(
         mut,
         close = player,
-        seeds = [b"bet", vault.key().as_ref(), bet.seed.to_le_bytes().as_ref()],
+        has_one = player,
+        seeds = [b"bet", vault.key().as_ref(), bet.seed_prefix().as_ref(), bet.seed_bytes().as_ref()],
         bump = bet.bump
     )]
     pub bet: Account<'info, Bet>,
+    #[account(
+        mut,
+        has_one = house,
+        seeds = [b"round", house.key().as_ref(), config.namespace.as_slice(), round.round_id.to_le_bytes().as_ref()],
+        bump = round.bump
+    )]
+    pub round: Option<Account<'info, Round>>,
+    // Insurance fund, funded by premiums and topped up by the house
+    #[account(
+        mut,
+        seeds = [b"insurance", house.key().as_ref(), config.namespace.as_slice()],
+        bump
+    )]
+    pub insurance: Option<SystemAccount<'info>>,
+    #[account(
+        seeds = [b"emergency"],
+        bump = emergency.bump
+    )]
+    pub emergency: Option<Account<'info, Emergency>>,
     pub system_program: Program<'info, System>
 }
 
 impl<'info> RefundBet<'info> {
-    pub fn refund_bet(&mut self, bumps: &BTreeMap<String, u8>) -> Result<()> {
+    pub fn refund_bet(&mut self, bumps: &BTreeMap<String, u8>, side_bets: &[AccountInfo<'info>]) -> Result<()> {
         let slot = Clock::get()?.slot;
-        require!((self.bet.slot - slot) > 1000, DiceError::TimeoutNotReached);
+        // Refunds don't wait for the expiry during an emergency
+        let emergency = matches!(&self.emergency, Some(emergency) if emergency.is_active(slot));
+        require!(
+            self.bet.is_expired(slot) || emergency,
+            DiceError::TimeoutNotReached
+        );
+
+        self.refund(bumps, self.player.to_account_info(), self.bet.amount)?;
+        if self.bet.premium != 0 {
+            self.refund_premium(bumps)?;
+        }
+
+        // A refunded bet counts as neither wagered nor won
+        match &mut self.round {
+            Some(round) => {
+                require_eq!(round.round_id, self.bet.round_id, DiceError::RoundMismatch);
+                round.settle(self.bet.player, 0, 0)?;
+            },
+            None => require_eq!(self.bet.round_id, 0, DiceError::RoundMismatch)
+        }
+
+        // Side bets can't outlive the bet they're on, so refund them all too
+        require_eq!(side_bets.len() % 2, 0, DiceError::SideBetAccounts);
+        require_eq!(side_bets.len() / 2, self.bet.side_bets as usize, DiceError::SideBetAccounts);
+        for pair in side_bets.chunks(2) {
+            let [side_bet_info, bettor] = pair else {
+                return err!(DiceError::SideBetAccounts);
+            };
+            let side_bet = Account::<SideBet>::try_from(side_bet_info)?;
+            require_keys_eq!(side_bet.bet, self.bet.key(), DiceError::SideBetAccounts);
+            require_keys_eq!(side_bet.bettor, bettor.key(), DiceError::SideBetAccounts);
+            self.refund(bumps, bettor.clone(), side_bet.amount)?;
+            side_bet.close(bettor.clone())?;
+        }
+        Ok(())
+    }
+
+    // Capped at what the fund holds above rent, as in resolve_bet
+    fn refund_premium(&self, bumps: &BTreeMap<String, u8>) -> Result<()> {
+        let insurance = self.insurance.as_ref().ok_or(DiceError::InsuranceAccount)?;
+        let available = insurance.lamports().saturating_sub(Rent::get()?.minimum_balance(0));
+
         let accounts = Transfer {
-            from: self.vault.to_account_info(),
+            from: insurance.to_account_info(),
             to: self.player.to_account_info()
         };
 
-        let seeds = [b"vault", &self.house.key().to_bytes()[..], &[*bumps.get("vault").ok_or(DiceError::BumpError)?]];
+        let seeds = [b"insurance", &self.house.key().to_bytes()[..], self.config.namespace.as_slice(), &[*bumps.get("insurance").ok_or(DiceError::BumpError)?]];
+        let signer_seeds = &[&seeds[..]][..];
+
+        let ctx = CpiContext::new_with_signer(
+            self.system_program.to_account_info(),
+            accounts,
+            signer_seeds
+        );
+        transfer(ctx, self.bet.premium.min(available))
+    }
+
+    fn refund(&self, bumps: &BTreeMap<String, u8>, to: AccountInfo<'info>, amount: u64) -> Result<()> {
+        let accounts = Transfer {
+            from: self.vault.to_account_info(),
+            to
+        };
+
+        let seeds = [b"vault", &self.house.key().to_bytes()[..], self.config.namespace.as_slice(), &[*bumps.get("vault").ok_or(DiceError::BumpError)?]];
         let signer_seeds = &[&seeds[..]][..];
-    
 
         let ctx = CpiContext::new_with_signer(
             self.system_program.to_account_info(),
@@ -45,6 +133,6 @@ impl<'info> RefundBet<'info> {
             signer_seeds
         );
 
-        transfer(ctx, self.bet.amount)
+        transfer(ctx, amount)
     }
 }
\ No newline at end of file
@@ -0,0 +1,102 @@
+use std::collections::BTreeMap;
+
+use anchor_lang::{prelude::*, system_program::{Transfer, transfer}, AccountsClose};
+
+use crate::{state::{Bet, Config, Emergency, Round}, errors::DiceError};
+
+#[derive(Accounts)]
+pub struct RefundBets<'info> {
+    // Anyone may sweep, funds only ever go back to each bet's player
+    pub keeper: Signer<'info>,
+    pub house: SystemAccount<'info>,
+    #[account(
+        mut,
+        seeds = [b"vault", house.key().as_ref(), config.namespace.as_slice()],
+        bump
+    )]
+    pub vault: SystemAccount<'info>,
+    #[account(
+        seeds = [b"config", house.key().as_ref(), config.namespace.as_slice()],
+        bump = config.bump
+    )]
+    pub config: Account<'info, Config>,
+    // Settles bets from this round, so an abandoned bet can't hold up
+    // finalize_round
+    #[account(
+        mut,
+        has_one = house,
+        seeds = [b"round", house.key().as_ref(), config.namespace.as_slice(), round.round_id.to_le_bytes().as_ref()],
+        bump = round.bump
+    )]
+    pub round: Option<Account<'info, Round>>,
+    #[account(
+        seeds = [b"emergency"],
+        bump = emergency.bump
+    )]
+    pub emergency: Option<Account<'info, Emergency>>,
+    pub system_program: Program<'info, System>
+}
+
+impl<'info> RefundBets<'info> {
+    pub fn refund_bets(&mut self, bumps: &BTreeMap<String, u8>, bets: &[AccountInfo<'info>]) -> Result<()> {
+        let slot = Clock::get()?.slot;
+        // Refunds don't wait for the expiry during an emergency
+        let emergency = matches!(&self.emergency, Some(emergency) if emergency.is_active(slot));
+
+        // Remaining accounts are (bet, player) pairs
+        require_eq!(bets.len() % 2, 0, DiceError::RefundAccounts);
+        for pair in bets.chunks(2) {
+            let [bet_info, player] = pair else {
+                return err!(DiceError::RefundAccounts);
+            };
+            let bet = Account::<Bet>::try_from(bet_info)?;
+
+            // Make sure the bet belongs to this vault
+            let address = Pubkey::create_program_address(
+                &[b"bet", self.vault.key().as_ref(), bet.seed_prefix().as_ref(), bet.seed_bytes().as_ref(), &[bet.bump]],
+                &crate::ID
+            ).map_err(|_| DiceError::RefundAccounts)?;
+            require_keys_eq!(address, bet_info.key(), DiceError::RefundAccounts);
+            require_keys_eq!(bet.player, player.key(), DiceError::RefundAccounts);
+
+            // Bets with side bets or insured need refund_bet to settle those
+            // too
+            require_eq!(bet.side_bets, 0, DiceError::RefundAccounts);
+            require_eq!(bet.premium, 0, DiceError::RefundAccounts);
+
+            require!(
+                bet.is_expired(slot) || emergency,
+                DiceError::TimeoutNotReached
+            );
+
+            // As in refund_bet, a refunded bet counts as neither wagered nor won
+            if bet.round_id != 0 {
+                let round = self.round.as_mut().ok_or(DiceError::RoundMismatch)?;
+                require_eq!(round.round_id, bet.round_id, DiceError::RoundMismatch);
+                round.settle(bet.player, 0, 0)?;
+            }
+
+            self.refund(bumps, player.clone(), bet.amount)?;
+            bet.close(player.clone())?;
+        }
+        Ok(())
+    }
+
+    fn refund(&self, bumps: &BTreeMap<String, u8>, to: AccountInfo<'info>, amount: u64) -> Result<()> {
+        let accounts = Transfer {
+            from: self.vault.to_account_info(),
+            to
+        };
+
+        let seeds = [b"vault", &self.house.key().to_bytes()[..], self.config.namespace.as_slice(), &[*bumps.get("vault").ok_or(DiceError::BumpError)?]];
+        let signer_seeds = &[&seeds[..]][..];
+
+        let ctx = CpiContext::new_with_signer(
+            self.system_program.to_account_info(),
+            accounts,
+            signer_seeds
+        );
+
+        transfer(ctx, amount)
+    }
+}
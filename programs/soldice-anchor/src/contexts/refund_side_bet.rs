@@ -0,0 +1,77 @@
+use std::collections::BTreeMap;
+
+use anchor_lang::{prelude::*, system_program::{Transfer, transfer}};
+
+use crate::{state::{Bet, Config, Emergency, SideBet}, errors::DiceError};
+
+#[derive(Accounts)]
+pub struct RefundSideBet<'info> {
+    #[account(mut)]
+    pub bettor: Signer<'info>,
+    pub house: SystemAccount<'info>,
+    #[account(
+        mut,
+        seeds = [b"vault", house.key().as_ref(), config.namespace.as_slice()],
+        bump
+    )]
+    pub vault: SystemAccount<'info>,
+    #[account(
+        has_one = house,
+        seeds = [b"config", house.key().as_ref(), config.namespace.as_slice()],
+        bump = config.bump
+    )]
+    pub config: Account<'info, Config>,
+    #[account(
+        mut,
+        seeds = [b"bet", vault.key().as_ref(), bet.seed_prefix().as_ref(), bet.seed_bytes().as_ref()],
+        bump = bet.bump
+    )]
+    pub bet: Account<'info, Bet>,
+    #[account(
+        mut,
+        close = bettor,
+        has_one = bettor,
+        has_one = bet,
+        seeds = [b"side", bet.key().as_ref(), bettor.key().as_ref()],
+        bump = side_bet.bump
+    )]
+    pub side_bet: Account<'info, SideBet>,
+    #[account(
+        seeds = [b"emergency"],
+        bump = emergency.bump
+    )]
+    pub emergency: Option<Account<'info, Emergency>>,
+    pub system_program: Program<'info, System>
+}
+
+impl<'info> RefundSideBet<'info> {
+    // Same conditions as refund_bet, so a side bettor isn't stuck waiting on
+    // the player to refund
+    pub fn refund_side_bet(&mut self, bumps: &BTreeMap<String, u8>) -> Result<()> {
+        let slot = Clock::get()?.slot;
+        let emergency = matches!(&self.emergency, Some(emergency) if emergency.is_active(slot));
+        require!(
+            self.bet.is_expired(slot) || emergency,
+            DiceError::TimeoutNotReached
+        );
+
+        // The bet no longer needs this side bet to settle
+        self.bet.side_bets = self.bet.side_bets.checked_sub(1).ok_or(DiceError::Overflow)?;
+
+        let accounts = Transfer {
+            from: self.vault.to_account_info(),
+            to: self.bettor.to_account_info()
+        };
+
+        let seeds = [b"vault", &self.house.key().to_bytes()[..], self.config.namespace.as_slice(), &[*bumps.get("vault").ok_or(DiceError::BumpError)?]];
+        let signer_seeds = &[&seeds[..]][..];
+
+        let ctx = CpiContext::new_with_signer(
+            self.system_program.to_account_info(),
+            accounts,
+            signer_seeds
+        );
+
+        transfer(ctx, self.side_bet.amount)
+    }
+}
@@ -1,112 +1,274 @@
 use std::collections::BTreeMap;
 
-use anchor_lang::{prelude::*, system_program::{Transfer, transfer}};
-use anchor_instruction_sysvar::{Ed25519InstructionSignatures, InstructionSysvar};
-use solana_program::{sysvar::instructions::load_instruction_at_checked, ed25519_program, hash::hash};
+use anchor_lang::{prelude::*, system_program::{Transfer, transfer}, AccountsClose, Discriminator};
+use solana_program::sysvar::instructions::load_instruction_at_checked;
 
-use crate::{state::Bet, errors::DiceError};
-
-
-pub const HOUSE_EDGE: u16 = 150; // 1.5% House edge
+use crate::{instruction, state::{Bet, Claim, Config, Emergency, Round, SideBet}, errors::DiceError, message::encode_bet_message, rng::derive_roll, memo::{MEMO_PROGRAM_ID, format_outcome_memo, write_memo}, fairness::{GAME_DICE, result_commitment}, events::{BetResolved, BET_RESOLVED_VERSION}, ed25519::{previous_ix_index, verify_ed25519_ix}};
 
 #[derive(Accounts)]
 pub struct ResolveBet<'info> {
     #[account(mut)]
     pub house: Signer<'info>,
     #[account(
-        mut
+        mut,
+        constraint = player.key() != house.key() @ DiceError::PlayerIsHouse,
+        constraint = player.key() != vault.key() @ DiceError::PlayerIsVault
     )]
-    ///CHECK: This is safe
-    pub player: UncheckedAccount<'info>,
+    pub player: SystemAccount<'info>,
     #[account(
         mut,
-        seeds = [b"vault", house.key().as_ref()],
+        seeds = [b"vault", house.key().as_ref(), config.namespace.as_slice()],
         bump
     )]
     pub vault: SystemAccount<'info>,
+    #[account(
+        mut,
+        seeds = [b"config", house.key().as_ref(), config.namespace.as_slice()],
+        bump = config.bump
+    )]
+    pub config: Account<'info, Config>,
     #[account(
         mut,
         close = player,
-        seeds = [b"bet", vault.key().as_ref(), bet.seed.to_le_bytes().as_ref()],
+        has_one = player,
+        seeds = [b"bet", vault.key().as_ref(), bet.seed_prefix().as_ref(), bet.seed_bytes().as_ref()],
         bump = bet.bump
     )]
     pub bet: Account<'info, Bet>,
+    #[account(
+        mut,
+        has_one = house,
+        seeds = [b"round", house.key().as_ref(), config.namespace.as_slice(), round.round_id.to_le_bytes().as_ref()],
+        bump = round.bump
+    )]
+    pub round: Option<Account<'info, Round>>,
+    #[account(
+        mut,
+        has_one = player,
+        seeds = [b"claim", vault.key().as_ref(), player.key().as_ref()],
+        bump = claim.bump
+    )]
+    pub claim: Option<Account<'info, Claim>>,
+    #[account(
+        address = MEMO_PROGRAM_ID
+    )]
+    /// CHECK: Only ever invoked, and pinned to the SPL Memo program
+    pub memo_program: Option<UncheckedAccount<'info>>,
     #[account(
         address = solana_program::sysvar::instructions::ID
     )]
     /// CHECK: This is safe
     pub instruction_sysvar: AccountInfo<'info>,
+    // Insurance fund, funded by premiums and topped up by the house
+    #[account(
+        mut,
+        seeds = [b"insurance", house.key().as_ref(), config.namespace.as_slice()],
+        bump
+    )]
+    pub insurance: Option<SystemAccount<'info>>,
+    #[account(
+        seeds = [b"emergency"],
+        bump
+    )]
+    /// CHECK: Pinned to the emergency PDA, which may not be initialized yet
+    pub emergency: UncheckedAccount<'info>,
     pub system_program: Program<'info, System>
 }
 
 impl<'info> ResolveBet<'info> {
 
-    pub fn verify_ed25519_signature(&mut self, sig: &[u8]) -> Result<()> {
-        // Get the Ed25519 signature instruction 
-        let ix = load_instruction_at_checked(
-            0, 
-            &self.instruction_sysvar.to_account_info()
+    pub fn verify_single_resolution(&self) -> Result<()> {
+        // Only one resolve_bet per vault per transaction, so each one sees
+        // the vault and config state it was priced against
+        let sysvar = self.instruction_sysvar.to_account_info();
+        let mut resolutions = 0;
+        let mut index = 0;
+        while let Ok(ix) = load_instruction_at_checked(index, &sysvar) {
+            if ix.program_id == crate::ID
+                && ix.data.get(..8) == Some(&instruction::ResolveBet::DISCRIMINATOR[..])
+                && ix.accounts.iter().any(|meta| meta.pubkey == self.vault.key()) {
+                resolutions += 1;
+            }
+            index += 1;
+        }
+        require_eq!(resolutions, 1, DiceError::MultipleResolutions);
+        Ok(())
+    }
+
+    // Returns the signature the precompile verified, which is what the roll
+    // is derived from
+    pub fn verify_ed25519_signature(&mut self, sig: &[u8]) -> Result<[u8; 64]> {
+        let signature = verify_ed25519_ix(
+            &self.instruction_sysvar,
+            previous_ix_index(&self.instruction_sysvar)?,
+            &self.house.key(),
+            &encode_bet_message(&self.bet)
         )?;
-        // Make sure the instruction is addressed to the ed25519 program
-        require_keys_eq!(ix.program_id, ed25519_program::ID, DiceError::Ed25519Program);
-        // Make sure there are no accounts present
-        require_eq!(ix.accounts.len(), 0, DiceError::Ed25519Accounts);
-        
-        let signatures = Ed25519InstructionSignatures::unpack(&ix.data)?.0;
 
-        require_eq!(signatures.len(), 1, DiceError::Ed25519DataLength);
-        let signature = &signatures[0];
+        // Ensure signatures match, unless the argument is ignored entirely
+        if !self.config.strict_signatures {
+            require!(signature[..] == sig[..], DiceError::Ed25519Signature);
+        }
 
-        // Make sure all the data is present to verify the signature
-        require!(signature.is_verifiable, DiceError::Ed25519Header);
-        
-        // Ensure public keys match
-        require_keys_eq!(signature.public_key.ok_or(DiceError::Ed25519Pubkey)?, self.house.key(), DiceError::Ed25519Pubkey);
+        Ok(signature)
+    }
 
-        // Ensure signatures match
-        require!(&signature.signature.ok_or(DiceError::Ed25519Signature)?.eq(sig), DiceError::Ed25519Signature);
+    pub fn resolve_bet(&mut self, bumps: &BTreeMap<String, u8>, sig: &[u8], side_bets: &[AccountInfo<'info>]) -> Result<()> {
+        let clock = Clock::get()?;
+        let slot = clock.slot;
+        require!(!Emergency::is_active_at(&self.emergency, slot)?, DiceError::EmergencyActive);
+        require!(!self.bet.is_expired(slot), DiceError::BetExpired);
+        // Applies to bets with no side bets too, or once the house signature
+        // is public someone could slip one in ahead of this resolution
+        require!(!self.bet.side_bets_open(slot), DiceError::SideBetsOpen);
 
-        // Ensure messages match
-        require!(&signature.message.as_ref().ok_or(DiceError::Ed25519Signature)?.eq(&self.bet.to_slice()), DiceError::Ed25519Signature);
+        let roll = derive_roll(self.bet.rng_version, sig)?;
+        require!(self.claim.is_some() == self.bet.claim_payout, DiceError::ClaimMismatch);
 
-        Ok(())
-    }
+        let won = roll < self.bet.roll;
+        let mut house_loss: u64 = 0;
+        let mut house_win: u64 = 0;
+        let mut player_payout: u64 = 0;
+        let mut wagered = self.bet.amount;
+        let mut paid: u64 = 0;
 
-    pub fn resolve_bet(&mut self, bumps: &BTreeMap<String, u8>, sig: &[u8]) -> Result<()> {
-        let hash = hash(sig).to_bytes();
-        let mut hash_16: [u8;16] = [0;16];
-        hash_16.copy_from_slice(&hash[0..16]);
-        let lower = u128::from_le_bytes(hash_16);
-        hash_16.copy_from_slice(&hash[16..32]);
-        let upper = u128::from_le_bytes(hash_16);
-        
-        let roll = lower
-            .wrapping_add(upper)
-            .wrapping_rem(100) as u8 + 1;
+        if won {
+            player_payout = self.win_payout(self.bet.amount)?;
+            match &mut self.claim {
+                // Stays in the vault until the player claims it
+                Some(claim) => claim.owed = claim.owed.checked_add(player_payout).ok_or(DiceError::Overflow)?,
+                None => self.pay(bumps, self.player.to_account_info(), player_payout)?
+            }
+            paid = paid.saturating_add(player_payout);
+            house_loss = house_loss.saturating_add(player_payout.saturating_sub(self.bet.amount));
+        } else {
+            house_win = house_win.saturating_add(self.bet.amount);
+            if self.bet.insurance_refund != 0 {
+                self.pay_insurance(bumps)?;
+            }
+        }
 
-        if self.bet.roll < roll {
+        match &mut self.round {
+            Some(round) => {
+                require_eq!(round.round_id, self.bet.round_id, DiceError::RoundMismatch);
+                round.settle(self.bet.player, self.bet.amount, player_payout)?;
+            },
+            None => require_eq!(self.bet.round_id, 0, DiceError::RoundMismatch)
+        }
 
-            // Payout minus house edge
-            let payout = (self.bet.amount as u128)
-            .checked_mul(10000 - HOUSE_EDGE as u128).ok_or(DiceError::Overflow)?
-            .checked_div(self.bet.roll as u128 - 1).ok_or(DiceError::Overflow)?
-            .checked_div(100).ok_or(DiceError::Overflow)? as u64;
-
-            let accounts = Transfer {
-                from: self.vault.to_account_info(),
-                to: self.player.to_account_info()
+        // Remaining accounts are (side_bet, bettor) pairs covering every open
+        // side bet, so the house can't leave out the ones it would lose
+        require_eq!(side_bets.len() % 2, 0, DiceError::SideBetAccounts);
+        require_eq!(side_bets.len() / 2, self.bet.side_bets as usize, DiceError::SideBetAccounts);
+        for pair in side_bets.chunks(2) {
+            let [side_bet_info, bettor] = pair else {
+                return err!(DiceError::SideBetAccounts);
             };
+            let side_bet = Account::<SideBet>::try_from(side_bet_info)?;
+            require_keys_eq!(side_bet.bet, self.bet.key(), DiceError::SideBetAccounts);
+            require_keys_eq!(side_bet.bettor, bettor.key(), DiceError::SideBetAccounts);
+            wagered = wagered.saturating_add(side_bet.amount);
 
-            let seeds = [b"vault", &self.house.key().to_bytes()[..], &[*bumps.get("vault").ok_or(DiceError::BumpError)?]];
-            let signer_seeds = &[&seeds[..]][..];
-    
-            let ctx = CpiContext::new_with_signer(
-                self.system_program.to_account_info(),
-                accounts,
-                signer_seeds
-            );
-            transfer(ctx, payout)?;
+            if side_bet.on_win == won {
+                let payout = if won {
+                    self.win_payout(side_bet.amount)?
+                } else {
+                    self.loss_payout(side_bet.amount)?
+                };
+                self.pay(bumps, bettor.clone(), payout)?;
+                paid = paid.saturating_add(payout);
+                house_loss = house_loss.saturating_add(payout.saturating_sub(side_bet.amount));
+            } else {
+                house_win = house_win.saturating_add(side_bet.amount);
+            }
+            side_bet.close(bettor.clone())?;
+        }
+
+        self.config.record_result(slot, house_loss, house_win);
+        if let Some(report) = self.config.record_volume(clock.unix_timestamp / 86400, wagered, paid) {
+            emit!(report);
+        }
+
+        emit!(BetResolved {
+            version: BET_RESOLVED_VERSION,
+            house: self.house.key(),
+            bet: self.bet.key(),
+            player: self.bet.player,
+            game_kind: GAME_DICE,
+            roll,
+            won,
+            payout: player_payout,
+            commitment: result_commitment(GAME_DICE, &self.bet.key(), sig, &[roll])
+        });
+
+        if let Some(memo_program) = &self.memo_program {
+            write_memo(&memo_program.to_account_info(), &format_outcome_memo(&self.bet.key(), roll, player_payout))?;
         }
         Ok(())
     }
+
+    fn win_payout(&self, amount: u64) -> Result<u64> {
+        match self.bet.multiplier {
+            // Payout minus house edge
+            0 => Ok((amount as u128)
+                .checked_mul(10000 - self.bet.house_edge as u128).ok_or(DiceError::Overflow)?
+                .checked_div(self.bet.roll as u128 - 1).ok_or(DiceError::Overflow)?
+                .checked_div(100).ok_or(DiceError::Overflow)? as u64),
+            // Payout table multiplier snapshotted at placement
+            multiplier => Ok((amount as u128)
+                .checked_mul(multiplier as u128).ok_or(DiceError::Overflow)?
+                .checked_div(10_000).ok_or(DiceError::Overflow)? as u64)
+        }
+    }
+
+    // Backing the player to lose wins on the other 101 - roll outcomes
+    fn loss_payout(&self, amount: u64) -> Result<u64> {
+        Ok((amount as u128)
+            .checked_mul(10000 - self.bet.house_edge as u128).ok_or(DiceError::Overflow)?
+            .checked_div(101 - self.bet.roll as u128).ok_or(DiceError::Overflow)?
+            .checked_div(100).ok_or(DiceError::Overflow)? as u64)
+    }
+
+    // Paid from the insurance fund rather than the vault, capped at what the
+    // fund holds above rent so a dry fund can't block the resolution
+    fn pay_insurance(&self, bumps: &BTreeMap<String, u8>) -> Result<()> {
+        let insurance = self.insurance.as_ref().ok_or(DiceError::InsuranceAccount)?;
+        let available = insurance.lamports().saturating_sub(Rent::get()?.minimum_balance(0));
+        let amount = ((self.bet.amount as u128)
+            .checked_mul(self.bet.insurance_refund as u128).ok_or(DiceError::Overflow)?
+            .checked_div(10_000).ok_or(DiceError::Overflow)? as u64)
+            .min(available);
+
+        let accounts = Transfer {
+            from: insurance.to_account_info(),
+            to: self.player.to_account_info()
+        };
+
+        let seeds = [b"insurance", &self.house.key().to_bytes()[..], self.config.namespace.as_slice(), &[*bumps.get("insurance").ok_or(DiceError::BumpError)?]];
+        let signer_seeds = &[&seeds[..]][..];
+
+        let ctx = CpiContext::new_with_signer(
+            self.system_program.to_account_info(),
+            accounts,
+            signer_seeds
+        );
+        transfer(ctx, amount)
+    }
+
+    fn pay(&self, bumps: &BTreeMap<String, u8>, to: AccountInfo<'info>, amount: u64) -> Result<()> {
+        let accounts = Transfer {
+            from: self.vault.to_account_info(),
+            to
+        };
+
+        let seeds = [b"vault", &self.house.key().to_bytes()[..], self.config.namespace.as_slice(), &[*bumps.get("vault").ok_or(DiceError::BumpError)?]];
+        let signer_seeds = &[&seeds[..]][..];
+
+        let ctx = CpiContext::new_with_signer(
+            self.system_program.to_account_info(),
+            accounts,
+            signer_seeds
+        );
+        transfer(ctx, amount)
+    }
 }
\ No newline at end of file
@@ -0,0 +1,27 @@
+use anchor_lang::prelude::*;
+
+use crate::state::Emergency;
+
+#[derive(Accounts)]
+pub struct SetEmergency<'info> {
+    pub authority: Signer<'info>,
+    #[account(
+        mut,
+        has_one = authority,
+        seeds = [b"emergency"],
+        bump = emergency.bump
+    )]
+    pub emergency: Account<'info, Emergency>
+}
+
+impl<'info> SetEmergency<'info> {
+    pub fn set_emergency(&mut self, active: bool) -> Result<()> {
+        let slot = Clock::get()?.slot;
+        if active {
+            self.emergency.arm(slot);
+        } else {
+            self.emergency.lift(slot);
+        }
+        Ok(())
+    }
+}
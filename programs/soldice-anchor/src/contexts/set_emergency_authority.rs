@@ -0,0 +1,24 @@
+use anchor_lang::prelude::*;
+
+use crate::state::Emergency;
+
+#[derive(Accounts)]
+pub struct SetEmergencyAuthority<'info> {
+    pub authority: Signer<'info>,
+    // Must sign too, so the switch can't be handed to a key nobody holds
+    pub new_authority: Signer<'info>,
+    #[account(
+        mut,
+        has_one = authority,
+        seeds = [b"emergency"],
+        bump = emergency.bump
+    )]
+    pub emergency: Account<'info, Emergency>
+}
+
+impl<'info> SetEmergencyAuthority<'info> {
+    pub fn set_emergency_authority(&mut self) -> Result<()> {
+        self.emergency.authority = self.new_authority.key();
+        Ok(())
+    }
+}
@@ -0,0 +1,49 @@
+use std::collections::BTreeMap;
+
+use anchor_lang::prelude::*;
+
+use crate::{state::{Config, Emergency, PayoutTable}, errors::DiceError};
+
+#[derive(Accounts)]
+pub struct SetPayoutTable<'info> {
+    #[account(mut)]
+    pub house: Signer<'info>,
+    #[account(
+        mut,
+        has_one = house,
+        seeds = [b"config", house.key().as_ref(), config.namespace.as_slice()],
+        bump = config.bump
+    )]
+    pub config: Account<'info, Config>,
+    #[account(
+        init,
+        payer = house,
+        space = PayoutTable::LEN,
+        seeds = [b"payouts", house.key().as_ref(), config.namespace.as_slice()],
+        bump
+    )]
+    pub payout_table: Account<'info, PayoutTable>,
+    #[account(
+        seeds = [b"emergency"],
+        bump
+    )]
+    /// CHECK: Pinned to the emergency PDA, which may not be initialized yet
+    pub emergency: UncheckedAccount<'info>,
+    pub system_program: Program<'info, System>
+}
+
+impl<'info> SetPayoutTable<'info> {
+    pub fn set_payout_table(&mut self, bumps: &BTreeMap<String, u8>, multipliers: [u32; 95]) -> Result<()> {
+        let clock = Clock::get()?;
+        require!(!Emergency::is_active_at(&self.emergency, clock.slot)?, DiceError::EmergencyActive);
+        self.payout_table.house = self.house.key();
+        self.payout_table.multipliers = multipliers;
+        self.payout_table.bump = *bumps.get("payout_table").ok_or(DiceError::BumpError)?;
+        let old = self.config.terms();
+        self.config.payout_table = true;
+        self.config.validate(Some(&*self.payout_table))?;
+        let change = self.config.record_change(old, &clock);
+        emit!(change);
+        Ok(())
+    }
+}
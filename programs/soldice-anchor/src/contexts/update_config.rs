@@ -0,0 +1,91 @@
+use anchor_lang::prelude::*;
+
+use crate::{state::{Config, Emergency}, errors::DiceError, events::ConfigTerms};
+
+#[derive(Accounts)]
+pub struct UpdateConfig<'info> {
+    pub house: Signer<'info>,
+    #[account(
+        mut,
+        has_one = house,
+        seeds = [b"config", house.key().as_ref(), config.namespace.as_slice()],
+        bump = config.bump
+    )]
+    pub config: Account<'info, Config>,
+    #[account(
+        seeds = [b"emergency"],
+        bump
+    )]
+    /// CHECK: Pinned to the emergency PDA, which may not be initialized yet
+    pub emergency: UncheckedAccount<'info>
+}
+
+impl<'info> UpdateConfig<'info> {
+    pub fn set_loss_limit(&mut self, loss_limit: u64, loss_window: u64, cooldown: u64) -> Result<()> {
+        let old = self.config.terms();
+        self.config.loss_limit = loss_limit;
+        self.config.loss_window = loss_window;
+        self.config.cooldown = cooldown;
+        self.config.window_start = Clock::get()?.slot;
+        self.config.window_losses = 0;
+        self.commit(old)
+    }
+
+    pub fn set_expiry(&mut self, expiry_slots: u64) -> Result<()> {
+        let old = self.config.terms();
+        self.config.expiry_slots = expiry_slots;
+        self.commit(old)
+    }
+
+    pub fn set_strict_nonce(&mut self, strict_nonce: bool) -> Result<()> {
+        let old = self.config.terms();
+        self.config.strict_nonce = strict_nonce;
+        self.commit(old)
+    }
+
+    pub fn set_strict_signatures(&mut self, strict_signatures: bool) -> Result<()> {
+        let old = self.config.terms();
+        self.config.strict_signatures = strict_signatures;
+        self.commit(old)
+    }
+
+    pub fn set_claim_payouts(&mut self, claim_payouts: bool) -> Result<()> {
+        let old = self.config.terms();
+        self.config.claim_payouts = claim_payouts;
+        self.commit(old)
+    }
+
+    pub fn set_house_edge(&mut self, house_edge: u16) -> Result<()> {
+        let old = self.config.terms();
+        self.config.house_edge = house_edge;
+        self.commit(old)
+    }
+
+    pub fn set_insurance(&mut self, insurance_premium: u16, insurance_refund: u16) -> Result<()> {
+        let old = self.config.terms();
+        self.config.insurance_premium = insurance_premium;
+        self.config.insurance_refund = insurance_refund;
+        self.commit(old)
+    }
+
+    pub fn set_relayer_surcharge(&mut self, relayer_surcharge: u16) -> Result<()> {
+        let old = self.config.terms();
+        self.config.relayer_surcharge = relayer_surcharge;
+        self.commit(old)
+    }
+
+    pub fn set_compact_seeds(&mut self, compact_seeds: bool) -> Result<()> {
+        let old = self.config.terms();
+        self.config.compact_seeds = compact_seeds;
+        self.commit(old)
+    }
+
+    fn commit(&mut self, old: ConfigTerms) -> Result<()> {
+        let clock = Clock::get()?;
+        require!(!Emergency::is_active_at(&self.emergency, clock.slot)?, DiceError::EmergencyActive);
+        self.config.validate(None)?;
+        let change = self.config.record_change(old, &clock);
+        emit!(change);
+        Ok(())
+    }
+}
@@ -0,0 +1,31 @@
+use anchor_lang::prelude::*;
+
+use crate::{state::Config, message::encode_resolver_challenge, ed25519::{previous_ix_index, verify_ed25519_ix}};
+
+#[derive(Accounts)]
+pub struct VerifyResolver<'info> {
+    pub house: SystemAccount<'info>,
+    #[account(
+        has_one = house,
+        seeds = [b"config", house.key().as_ref(), config.namespace.as_slice()],
+        bump = config.bump
+    )]
+    pub config: Account<'info, Config>,
+    #[account(
+        address = solana_program::sysvar::instructions::ID
+    )]
+    /// CHECK: This is safe
+    pub instruction_sysvar: AccountInfo<'info>
+}
+
+impl<'info> VerifyResolver<'info> {
+    pub fn verify_resolver(&self) -> Result<()> {
+        verify_ed25519_ix(
+            &self.instruction_sysvar,
+            previous_ix_index(&self.instruction_sysvar)?,
+            &self.house.key(),
+            &encode_resolver_challenge(&self.house.key())
+        )?;
+        Ok(())
+    }
+}
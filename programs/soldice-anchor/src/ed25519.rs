@@ -0,0 +1,107 @@
+use anchor_lang::prelude::*;
+use solana_program::{sysvar::instructions::{load_current_index_checked, load_instruction_at_checked}, instruction::{get_stack_height, TRANSACTION_LEVEL_STACK_HEIGHT}, ed25519_program};
+
+use crate::errors::DiceError;
+
+// Layout produced by Ed25519InstructionOffsets::new and web3.js for a single
+// signature: header, then pubkey, signature and message back to back
+const PUBKEY_OFFSET: u16 = 16;
+const SIGNATURE_OFFSET: u16 = PUBKEY_OFFSET + 32;
+const MESSAGE_OFFSET: u16 = SIGNATURE_OFFSET + 64;
+
+// Rejects any other arrangement of offsets, so the precompile can't be fed
+// overlapping or aliased windows over the same bytes
+pub fn validate_canonical_layout(data: &[u8], message_len: usize) -> Result<()> {
+    let message_size = u16::try_from(message_len).map_err(|_| DiceError::Ed25519Layout)?;
+    let mut header = vec![1u8, 0];
+    for offset in [
+        SIGNATURE_OFFSET, u16::MAX,
+        PUBKEY_OFFSET, u16::MAX,
+        MESSAGE_OFFSET, message_size, u16::MAX
+    ] {
+        header.extend_from_slice(&offset.to_le_bytes());
+    }
+    require!(data.get(..PUBKEY_OFFSET as usize) == Some(&header[..]), DiceError::Ed25519Layout);
+    require_eq!(data.len(), MESSAGE_OFFSET as usize + message_len, DiceError::Ed25519Layout);
+    Ok(())
+}
+
+// Index of the instruction immediately before the executing one, so other
+// instructions (e.g. compute budget) can come first in the transaction.
+// The sysvar's current index is the top-level instruction's, so under CPI it
+// would point next to the caller instead; only top-level calls are allowed.
+pub fn previous_ix_index(instruction_sysvar: &AccountInfo) -> Result<usize> {
+    require_eq!(get_stack_height(), TRANSACTION_LEVEL_STACK_HEIGHT, DiceError::NotTopLevel);
+    let current = load_current_index_checked(instruction_sysvar)? as usize;
+    let previous = current.checked_sub(1).ok_or(DiceError::Ed25519Program)?;
+    Ok(previous)
+}
+
+pub fn count_ed25519_instructions(instruction_sysvar: &AccountInfo) -> Result<u16> {
+    let mut count: u16 = 0;
+    let mut index = 0;
+    while let Ok(ix) = load_instruction_at_checked(index, instruction_sysvar) {
+        if ix.program_id == ed25519_program::ID {
+            count = count.checked_add(1).ok_or(DiceError::Overflow)?;
+        }
+        index += 1;
+    }
+    Ok(count)
+}
+
+// Extra precompile instructions elsewhere in the transaction can't be used
+// to confuse the index-based lookup
+pub fn assert_single_ed25519(instruction_sysvar: &AccountInfo) -> Result<()> {
+    require_eq!(count_ed25519_instructions(instruction_sysvar)?, 1, DiceError::Ed25519Instructions);
+    Ok(())
+}
+
+// Checks that the Ed25519 precompile instruction at `ix_index` verified
+// exactly one signature by `pubkey` over `message`, and returns its bytes.
+// It must also be the only Ed25519 instruction in the transaction.
+pub fn verify_ed25519_ix(instruction_sysvar: &AccountInfo, ix_index: usize, pubkey: &Pubkey, message: &[u8]) -> Result<[u8; 64]> {
+    assert_single_ed25519(instruction_sysvar)?;
+    // Get the Ed25519 signature instruction 
+    let ix = load_instruction_at_checked(
+        ix_index, 
+        instruction_sysvar
+    )?;
+    // Make sure the instruction is addressed to the ed25519 program
+    require_keys_eq!(ix.program_id, ed25519_program::ID, DiceError::Ed25519Program);
+    // Make sure there are no accounts present
+    require_eq!(ix.accounts.len(), 0, DiceError::Ed25519Accounts);
+    let signature = unpack_single(&ix.data, message.len())?;
+
+    // Ensure public keys match
+    require_keys_eq!(signature.public_key, *pubkey, DiceError::Ed25519Pubkey);
+
+    // Ensure messages match
+    require!(signature.message == message, DiceError::Ed25519Message);
+
+    Ok(signature.signature)
+}
+
+pub struct Ed25519Single<'a> {
+    pub public_key: Pubkey,
+    pub signature: [u8; 64],
+    pub message: &'a [u8]
+}
+
+// Reads the one signature straight from its fixed offsets. The canonical
+// layout implies exactly one signature, all of it in this instruction, so
+// there is nothing left for a general unpack to check.
+pub fn unpack_single(data: &[u8], message_len: usize) -> Result<Ed25519Single<'_>> {
+    validate_canonical_layout(data, message_len)?;
+    let public_key = data.get(PUBKEY_OFFSET as usize..SIGNATURE_OFFSET as usize)
+        .and_then(|bytes| <[u8; 32]>::try_from(bytes).ok())
+        .ok_or(DiceError::Ed25519Pubkey)?;
+    let signature = data.get(SIGNATURE_OFFSET as usize..MESSAGE_OFFSET as usize)
+        .and_then(|bytes| <[u8; 64]>::try_from(bytes).ok())
+        .ok_or(DiceError::Ed25519Signature)?;
+    let message = data.get(MESSAGE_OFFSET as usize..).ok_or(DiceError::Ed25519Message)?;
+    Ok(Ed25519Single {
+        public_key: Pubkey::new_from_array(public_key),
+        signature,
+        message
+    })
+}
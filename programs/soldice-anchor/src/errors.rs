@@ -29,5 +29,85 @@ pub enum DiceError {
     #[msg("Ed25119 Accounts Error")]
     Ed25519Accounts,
     #[msg("Ed25119 Data Length Error")]
-    Ed25519DataLength
+    Ed25519DataLength,
+    #[msg("Vault is below the minimum bankroll")]
+    MinimumBankroll,
+    #[msg("Table is closed for cooldown")]
+    TableClosed,
+    #[msg("Loss window must be non-zero")]
+    InvalidLossWindow,
+    #[msg("Cooldown must be non-zero")]
+    InvalidCooldown,
+    #[msg("Bet has expired")]
+    BetExpired,
+    #[msg("Unsupported RNG version")]
+    RngVersion,
+    #[msg("Multiple resolutions for one vault in a transaction")]
+    MultipleResolutions,
+    #[msg("Payouts must not increase with the roll")]
+    PayoutCurve,
+    #[msg("Payouts must exceed the stake")]
+    PayoutMultiplier,
+    #[msg("Payouts must not exceed fair odds")]
+    PayoutEdge,
+    #[msg("Payout table does not match config")]
+    PayoutTableMismatch,
+    #[msg("Side bet accounts missing or mismatched")]
+    SideBetAccounts,
+    #[msg("Round is already finalized")]
+    RoundFinalized,
+    #[msg("Round is full")]
+    RoundFull,
+    #[msg("Round does not match bet")]
+    RoundMismatch,
+    #[msg("Round still has open bets")]
+    RoundOpenBets,
+    #[msg("Refund accounts missing or mismatched")]
+    RefundAccounts,
+    #[msg("Player nonce account required")]
+    NonceRequired,
+    #[msg("Seed does not match player nonce")]
+    NonceMismatch,
+    #[msg("Ed25519 Layout Error")]
+    Ed25519Layout,
+    #[msg("Instruction must be called at the top level, not via CPI")]
+    NotTopLevel,
+    #[msg("Claim account must be passed exactly when the bet pays out by claim")]
+    ClaimMismatch,
+    #[msg("Nothing to claim")]
+    NothingToClaim,
+    #[msg("Player can't be the house")]
+    PlayerIsHouse,
+    #[msg("Player can't be the vault")]
+    PlayerIsVault,
+    #[msg("House edge is above the maximum")]
+    InvalidHouseEdge,
+    #[msg("Only refunds, claims and insurance deposits are allowed during an emergency")]
+    EmergencyActive,
+    #[msg("Signer is not the program upgrade authority")]
+    NotUpgradeAuthority,
+    #[msg("Insurance rates must be at most 10000 bps, and a premium needs a refund")]
+    InvalidInsurance,
+    #[msg("Insurance is not offered")]
+    InsuranceDisabled,
+    #[msg("Insured bets need the insurance fund account")]
+    InsuranceAccount,
+    #[msg("Transaction must contain exactly one Ed25519 instruction")]
+    Ed25519Instructions,
+    #[msg("Bet already has the maximum number of side bets")]
+    TooManySideBets,
+    #[msg("Side bets on this bet have closed")]
+    SideBetsClosed,
+    #[msg("Bet can't resolve until side bets on it close")]
+    SideBetsOpen,
+    #[msg("Relayer surcharge is above the maximum")]
+    InvalidRelayerSurcharge,
+    #[msg("Compact seeds must fit in a u64")]
+    CompactSeed,
+    #[msg("Expiry must be 0, or past the side bet window and within the refund timeout")]
+    InvalidExpiry,
+    #[msg("Player has not been entered in the round")]
+    NotInRound,
+    #[msg("Emergency timelock is below the minimum")]
+    InvalidTimelock
 }
\ No newline at end of file
@@ -0,0 +1,74 @@
+//! Events emitted by the program.
+//!
+//! Every event leads with a `version` so indexers can decode logs from any
+//! program upgrade. Any change to an event's layout bumps its version
+//! constant, and that includes structs nested in it: a field added to
+//! `ConfigTerms` lands in the middle of `ConfigChanged`, between `old` and
+//! `new`, so decoders must pick the layout by version rather than read a
+//! prefix.
+
+use anchor_lang::prelude::*;
+
+pub const DAILY_REPORT_VERSION: u8 = 1;
+pub const CONFIG_CHANGED_VERSION: u8 = 1;
+pub const BET_RESOLVED_VERSION: u8 = 1;
+
+// Emitted by the first resolution of each UTC day, covering the last day
+// that saw any resolutions. Refunds aren't counted, so `house_net` is the
+// edge collected on resolved bets and side bets.
+#[event]
+pub struct DailyReport {
+    pub version: u8,
+    pub house: Pubkey,
+    // Days since the unix epoch
+    pub day: i64,
+    pub wagered: u64,
+    pub paid: u64,
+    pub house_net: i128
+}
+
+// The config settings that decide how bets are taken and paid
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq)]
+pub struct ConfigTerms {
+    pub house_edge: u16,
+    pub min_bankroll: u64,
+    pub loss_limit: u64,
+    pub loss_window: u64,
+    pub cooldown: u64,
+    pub expiry_slots: u64,
+    pub payout_table: bool,
+    pub strict_nonce: bool,
+    pub strict_signatures: bool,
+    pub claim_payouts: bool,
+    pub insurance_premium: u16,
+    pub insurance_refund: u16,
+    pub relayer_surcharge: u16,
+    pub compact_seeds: bool
+}
+
+// Emitted by every config write, starting with initialize at sequence 0, so
+// the terms in force at any slot can be rebuilt from the log
+#[event]
+pub struct ConfigChanged {
+    pub version: u8,
+    pub house: Pubkey,
+    pub sequence: u64,
+    pub slot: u64,
+    pub timestamp: i64,
+    pub old: ConfigTerms,
+    pub new: ConfigTerms
+}
+
+// Emitted by every resolution. See fairness.rs for the commitment.
+#[event]
+pub struct BetResolved {
+    pub version: u8,
+    pub house: Pubkey,
+    pub bet: Pubkey,
+    pub player: Pubkey,
+    pub game_kind: u8,
+    pub roll: u8,
+    pub won: bool,
+    pub payout: u64,
+    pub commitment: [u8; 32]
+}
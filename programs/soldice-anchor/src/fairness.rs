@@ -0,0 +1,20 @@
+//! Result commitment for third-party fairness checks.
+//!
+//! Every resolution event carries
+//!
+//! `sha256(RESULT_DOMAIN || game kind || bet address || signature || derived)`
+//!
+//! where `derived` is the game's outcome bytes. For dice (`GAME_DICE`) that
+//! is the single roll byte. A checker that recomputes the commitment from the
+//! event and the verified house signature can cover every game with one
+//! algorithm, then check `derived` against the game's own rng version.
+
+use anchor_lang::prelude::*;
+use solana_program::hash::hashv;
+
+pub const RESULT_DOMAIN: &[u8] = b"soldice:result";
+pub const GAME_DICE: u8 = 1;
+
+pub fn result_commitment(game_kind: u8, bet: &Pubkey, sig: &[u8], derived: &[u8]) -> [u8; 32] {
+    hashv(&[RESULT_DOMAIN, &[game_kind], bet.as_ref(), sig, derived]).to_bytes()
+}
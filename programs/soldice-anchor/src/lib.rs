@@ -1,9 +1,18 @@
+// On-chain code returns errors on malformed input rather than panicking
+#![deny(clippy::indexing_slicing)]
+
 use anchor_lang::prelude::*;
 
 mod contexts;
 use contexts::*;
 mod state;
 mod errors;
+pub mod message;
+pub mod rng;
+pub mod events;
+pub mod memo;
+pub mod fairness;
+mod ed25519;
 
 declare_id!("6a84EaQix213TCn5vPRZBPE6Zoc8hxJBN9ax1KJ55cdg");
 
@@ -11,7 +20,8 @@ declare_id!("6a84EaQix213TCn5vPRZBPE6Zoc8hxJBN9ax1KJ55cdg");
 pub mod soldice_anchor {
     use super::*;
 
-    pub fn initialize(ctx: Context<Initialize>, amount: u64) -> Result<()> {
+    pub fn initialize(ctx: Context<Initialize>, amount: u64, min_bankroll: u64, namespace: Vec<u8>) -> Result<()> {
+        ctx.accounts.init_config(&ctx.bumps, min_bankroll, namespace)?;
         ctx.accounts.init(amount)
     }
 
@@ -20,12 +30,118 @@ pub mod soldice_anchor {
         ctx.accounts.deposit(amount)
     }
 
-    pub fn resolve_bet(ctx: Context<ResolveBet>, sig: Vec<u8>) -> Result<()> {
-        ctx.accounts.verify_ed25519_signature(&sig)?;
-        ctx.accounts.resolve_bet(&ctx.bumps, &sig)
+    pub fn place_side_bet(ctx: Context<PlaceSideBet>, amount: u64, on_win: bool) -> Result<()> {
+        ctx.accounts.create_side_bet(&ctx.bumps, amount, on_win)?;
+        ctx.accounts.deposit(amount)
+    }
+
+    pub fn resolve_bet<'info>(ctx: Context<'_, '_, '_, 'info, ResolveBet<'info>>, sig: Vec<u8>) -> Result<()> {
+        ctx.accounts.verify_single_resolution()?;
+        let sig = ctx.accounts.verify_ed25519_signature(&sig)?;
+        ctx.accounts.resolve_bet(&ctx.bumps, &sig, ctx.remaining_accounts)
+    }
+
+    pub fn refund_side_bet(ctx: Context<RefundSideBet>) -> Result<()> {
+        ctx.accounts.refund_side_bet(&ctx.bumps)
+    }
+
+    pub fn refund_bet<'info>(ctx: Context<'_, '_, '_, 'info, RefundBet<'info>>) -> Result<()> {
+        ctx.accounts.refund_bet(&ctx.bumps, ctx.remaining_accounts)
+    }
+
+    pub fn refund_bets<'info>(ctx: Context<'_, '_, '_, 'info, RefundBets<'info>>) -> Result<()> {
+        ctx.accounts.refund_bets(&ctx.bumps, ctx.remaining_accounts)
+    }
+
+    pub fn set_loss_limit(ctx: Context<UpdateConfig>, loss_limit: u64, loss_window: u64, cooldown: u64) -> Result<()> {
+        ctx.accounts.set_loss_limit(loss_limit, loss_window, cooldown)
+    }
+
+    pub fn set_expiry(ctx: Context<UpdateConfig>, expiry_slots: u64) -> Result<()> {
+        ctx.accounts.set_expiry(expiry_slots)
+    }
+
+    pub fn set_strict_nonce(ctx: Context<UpdateConfig>, strict_nonce: bool) -> Result<()> {
+        ctx.accounts.set_strict_nonce(strict_nonce)
+    }
+
+    pub fn set_strict_signatures(ctx: Context<UpdateConfig>, strict_signatures: bool) -> Result<()> {
+        ctx.accounts.set_strict_signatures(strict_signatures)
+    }
+
+    pub fn set_claim_payouts(ctx: Context<UpdateConfig>, claim_payouts: bool) -> Result<()> {
+        ctx.accounts.set_claim_payouts(claim_payouts)
+    }
+
+    pub fn set_house_edge(ctx: Context<UpdateConfig>, house_edge: u16) -> Result<()> {
+        ctx.accounts.set_house_edge(house_edge)
+    }
+
+    pub fn set_insurance(ctx: Context<UpdateConfig>, insurance_premium: u16, insurance_refund: u16) -> Result<()> {
+        ctx.accounts.set_insurance(insurance_premium, insurance_refund)
+    }
+
+    pub fn set_relayer_surcharge(ctx: Context<UpdateConfig>, relayer_surcharge: u16) -> Result<()> {
+        ctx.accounts.set_relayer_surcharge(relayer_surcharge)
+    }
+
+    pub fn set_compact_seeds(ctx: Context<UpdateConfig>, compact_seeds: bool) -> Result<()> {
+        ctx.accounts.set_compact_seeds(compact_seeds)
+    }
+
+    pub fn fund_insurance(ctx: Context<FundInsurance>, amount: u64) -> Result<()> {
+        ctx.accounts.fund_insurance(amount)
+    }
+
+    pub fn init_nonce(ctx: Context<InitNonce>) -> Result<()> {
+        ctx.accounts.init_nonce(&ctx.bumps)
+    }
+
+    pub fn init_claim(ctx: Context<InitClaim>) -> Result<()> {
+        ctx.accounts.init_claim(&ctx.bumps)
+    }
+
+    pub fn claim_winnings(ctx: Context<ClaimWinnings>) -> Result<()> {
+        ctx.accounts.claim_winnings(&ctx.bumps)
+    }
+
+    pub fn init_emergency(ctx: Context<InitEmergency>, timelock: u64) -> Result<()> {
+        ctx.accounts.init_emergency(&ctx.bumps, timelock)
+    }
+
+    pub fn set_emergency(ctx: Context<SetEmergency>, active: bool) -> Result<()> {
+        ctx.accounts.set_emergency(active)
+    }
+
+    pub fn set_emergency_authority(ctx: Context<SetEmergencyAuthority>) -> Result<()> {
+        ctx.accounts.set_emergency_authority()
+    }
+
+    pub fn set_payout_table(ctx: Context<SetPayoutTable>, multipliers: [u32; 95]) -> Result<()> {
+        ctx.accounts.set_payout_table(&ctx.bumps, multipliers)
+    }
+
+    pub fn close_payout_table(ctx: Context<ClosePayoutTable>) -> Result<()> {
+        ctx.accounts.close_payout_table()
+    }
+
+    pub fn create_round(ctx: Context<CreateRound>, round_id: u64) -> Result<()> {
+        ctx.accounts.create_round(&ctx.bumps, round_id)
+    }
+
+    pub fn enter_round(ctx: Context<EnterRound>, player: Pubkey) -> Result<()> {
+        ctx.accounts.enter_round(player)
+    }
+
+    pub fn finalize_round(ctx: Context<FinalizeRound>) -> Result<()> {
+        ctx.accounts.finalize_round()
+    }
+
+    pub fn verify_resolver(ctx: Context<VerifyResolver>) -> Result<()> {
+        ctx.accounts.verify_resolver()
     }
 
-    pub fn refund_bet(ctx: Context<RefundBet>) -> Result<()> {
-        ctx.accounts.refund_bet(&ctx.bumps)
+    pub fn derive_addresses(ctx: Context<DeriveAddresses>, player: Pubkey, seed: u128) -> Result<Addresses> {
+        Ok(ctx.accounts.derive_addresses(player, seed))
     }
 }
@@ -0,0 +1,28 @@
+//! Human-readable outcome memo written through the SPL Memo program.
+//!
+//! resolve_bet writes one when the memo program is passed, so wallets that
+//! surface memos can show players the result without a custom frontend. The
+//! format is fixed so clients can also parse it:
+//!
+//! `soldice:v1 bet=<bet address> roll=<roll> payout=<lamports>`
+//!
+//! Any change to the format must bump `MEMO_VERSION`.
+
+use anchor_lang::{prelude::*, solana_program::{instruction::Instruction, program::invoke}};
+
+pub const MEMO_PROGRAM_ID: Pubkey = solana_program::pubkey!("MemoSq4gqABAXKb96qnH8TysNcWxMyWCqXgDLGmfcHr");
+pub const MEMO_VERSION: u8 = 1;
+
+pub fn format_outcome_memo(bet: &Pubkey, roll: u8, payout: u64) -> String {
+    format!("soldice:v{MEMO_VERSION} bet={bet} roll={roll} payout={payout}")
+}
+
+pub fn write_memo(memo_program: &AccountInfo, memo: &str) -> Result<()> {
+    let ix = Instruction {
+        program_id: MEMO_PROGRAM_ID,
+        accounts: vec![],
+        data: memo.as_bytes().to_vec()
+    };
+    invoke(&ix, &[memo_program.clone()])?;
+    Ok(())
+}
@@ -0,0 +1,179 @@
+//! Canonical encoding of the message the house signs to resolve a bet.
+//!
+//! This is the interface between the program and every off-chain signer, so
+//! the layout is fixed and versioned rather than derived from `Bet`'s Borsh
+//! representation. All integers are little-endian.
+//!
+//! Every message starts with `BET_MESSAGE_DOMAIN` and the program id, so a
+//! house signature for this program can't be replayed against another
+//! program that trusts the same key.
+//!
+//! | offset | size | field      |
+//! |--------|------|------------|
+//! | 0      | 15   | domain     |
+//! | 15     | 32   | program id |
+//! | 47     | 1    | version    |
+//! | 48     | 32   | player     |
+//! | 80     | 16   | seed       |
+//! | 96     | 8    | slot       |
+//! | 104    | 8    | amount     |
+//! | 112    | 1    | roll       |
+//! | 113    | 1    | bump       |
+//! | 114    | 1    | rng        |
+//! | 115    | 8    | expires at |
+//!
+//! `expires at` is the bet's `expires_at_slot`, fixed at placement from the
+//! house's expiry: resolve_bet rejects the signature from that slot on, when
+//! the bet becomes refundable, so a leaked signature is only usable for a
+//! bounded window. Every field
+//! comes from the bet, so there is exactly one message, and one signature,
+//! per bet.
+//!
+//! Any change to the layout must bump `BET_MESSAGE_VERSION`.
+
+use anchor_lang::prelude::*;
+
+use crate::state::Bet;
+
+pub const BET_MESSAGE_DOMAIN: &[u8; 15] = b"soldice:resolve";
+pub const BET_MESSAGE_VERSION: u8 = 1;
+pub const BET_MESSAGE_PREFIX_LEN: usize = 15 + 32;
+pub const BET_MESSAGE_LEN: usize = BET_MESSAGE_PREFIX_LEN + 1 + 32 + 16 + 8 + 8 + 1 + 1 + 1 + 8;
+
+// The bet fields the house signs over, in message order
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct BetMessage {
+    pub player: Pubkey,
+    pub seed: u128,
+    pub slot: u64,
+    pub amount: u64,
+    pub roll: u8,
+    pub bump: u8,
+    pub rng_version: u8,
+    pub expires_at_slot: u64
+}
+
+impl From<&Bet> for BetMessage {
+    fn from(bet: &Bet) -> Self {
+        Self {
+            player: bet.player,
+            seed: bet.seed,
+            slot: bet.slot,
+            amount: bet.amount,
+            roll: bet.roll,
+            bump: bet.bump,
+            rng_version: bet.rng_version,
+            expires_at_slot: bet.expires_at_slot
+        }
+    }
+}
+
+pub fn encode_bet_message(bet: &Bet) -> [u8; BET_MESSAGE_LEN] {
+    BetMessage::from(bet).encode()
+}
+
+impl BetMessage {
+    pub fn encode(&self) -> [u8; BET_MESSAGE_LEN] {
+        let fields: [&[u8]; 11] = [
+            BET_MESSAGE_DOMAIN,
+            &crate::ID.to_bytes(),
+            &[BET_MESSAGE_VERSION],
+            &self.player.to_bytes(),
+            &self.seed.to_le_bytes(),
+            &self.slot.to_le_bytes(),
+            &self.amount.to_le_bytes(),
+            &[self.roll],
+            &[self.bump],
+            &[self.rng_version],
+            &self.expires_at_slot.to_le_bytes()
+        ];
+        let mut m = [0u8; BET_MESSAGE_LEN];
+        m.iter_mut().zip(fields.into_iter().flatten()).for_each(|(dst, src)| *dst = *src);
+        m
+    }
+
+    // None unless `m` is exactly a current-version message for this program
+    pub fn decode(m: &[u8]) -> Option<Self> {
+        if m.len() != BET_MESSAGE_LEN
+            || m.get(..15) != Some(&BET_MESSAGE_DOMAIN[..])
+            || m.get(15..47) != Some(&crate::ID.to_bytes()[..])
+            || m.get(47) != Some(&BET_MESSAGE_VERSION) {
+            return None;
+        }
+        Some(Self {
+            player: Pubkey::new_from_array(m.get(48..80)?.try_into().ok()?),
+            seed: u128::from_le_bytes(m.get(80..96)?.try_into().ok()?),
+            slot: u64::from_le_bytes(m.get(96..104)?.try_into().ok()?),
+            amount: u64::from_le_bytes(m.get(104..112)?.try_into().ok()?),
+            roll: *m.get(112)?,
+            bump: *m.get(113)?,
+            rng_version: *m.get(114)?,
+            expires_at_slot: u64::from_le_bytes(m.get(115..123)?.try_into().ok()?)
+        })
+    }
+}
+
+// Signed by the house in `verify_resolver` to prove its key works end to
+// end. Cannot collide with a bet message, which starts with its own domain.
+pub const RESOLVER_CHALLENGE: &[u8] = b"soldice:verify_resolver";
+
+pub fn encode_resolver_challenge(house: &Pubkey) -> Vec<u8> {
+    [RESOLVER_CHALLENGE, house.as_ref()].concat()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn message() -> BetMessage {
+        BetMessage {
+            player: Pubkey::new_from_array([7; 32]),
+            seed: 0x0102_0304_0506_0708_090a_0b0c_0d0e_0f10,
+            slot: 1_000,
+            amount: 10_000_000,
+            roll: 50,
+            bump: 254,
+            rng_version: 1,
+            expires_at_slot: 1_150
+        }
+    }
+
+    #[test]
+    fn round_trips() {
+        assert_eq!(BetMessage::decode(&message().encode()), Some(message()));
+    }
+
+    #[test]
+    fn matches_documented_layout() {
+        let m = message().encode();
+        assert_eq!(m.len(), 123);
+        assert_eq!(m.get(..15), Some(&BET_MESSAGE_DOMAIN[..]));
+        assert_eq!(m.get(15..47), Some(&crate::ID.to_bytes()[..]));
+        assert_eq!(m.get(47), Some(&BET_MESSAGE_VERSION));
+        assert_eq!(m.get(48..80), Some(&[7u8; 32][..]));
+        assert_eq!(m.get(80..96), Some(&message().seed.to_le_bytes()[..]));
+        assert_eq!(m.get(96..104), Some(&1_000u64.to_le_bytes()[..]));
+        assert_eq!(m.get(104..112), Some(&10_000_000u64.to_le_bytes()[..]));
+        assert_eq!(m.get(112..115), Some(&[50, 254, 1][..]));
+        assert_eq!(m.get(115..), Some(&1_150u64.to_le_bytes()[..]));
+    }
+
+    #[test]
+    fn rejects_wrong_length() {
+        let m = message().encode();
+        assert_eq!(BetMessage::decode(&[]), None);
+        assert_eq!(BetMessage::decode(m.get(..BET_MESSAGE_LEN - 1).unwrap()), None);
+        assert_eq!(BetMessage::decode(&[m.as_slice(), &[0]].concat()), None);
+    }
+
+    #[test]
+    fn rejects_another_domain_program_or_version() {
+        for offset in [0, 15, 47] {
+            let mut m = message().encode();
+            if let Some(byte) = m.get_mut(offset) {
+                *byte ^= 0xff;
+            }
+            assert_eq!(BetMessage::decode(&m), None, "offset {offset}");
+        }
+    }
+}
@@ -0,0 +1,31 @@
+//! Roll derivation from the house signature.
+//!
+//! Each bet records the `rng_version` in force when it was placed and is
+//! always rolled with that scheme, so a new scheme only applies to bets
+//! placed after it ships.
+
+use anchor_lang::prelude::*;
+use solana_program::hash::hash;
+
+use crate::errors::DiceError;
+
+pub const RNG_VERSION: u8 = 1;
+
+pub fn derive_roll(rng_version: u8, sig: &[u8]) -> Result<u8> {
+    match rng_version {
+        1 => Ok(roll_v1(sig)),
+        _ => err!(DiceError::RngVersion)
+    }
+}
+
+// Sum of both halves of sha256(sig), mod 100, in 1..=100
+fn roll_v1(sig: &[u8]) -> u8 {
+    let hash = hash(sig).to_bytes();
+    let mut lower: [u8;16] = [0;16];
+    let mut upper: [u8;16] = [0;16];
+    lower.iter_mut().chain(upper.iter_mut()).zip(hash).for_each(|(dst, src)| *dst = src);
+
+    u128::from_le_bytes(lower)
+        .wrapping_add(u128::from_le_bytes(upper))
+        .wrapping_rem(100) as u8 + 1
+}
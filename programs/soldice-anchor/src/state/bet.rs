@@ -0,0 +1,80 @@
+use anchor_lang::prelude::*;
+
+#[account]
+pub struct Bet {
+    pub player: Pubkey,
+    pub seed: u128,
+    pub slot: u64,
+    pub amount: u64,
+    pub roll: u8,
+    pub bump : u8,
+    pub rng_version: u8,
+    pub side_bets: u16,
+    // 0 when the bet isn't part of a round
+    pub round_id: u64,
+    // Terms in force at placement, so later config changes can't alter them.
+    // House edge in basis points, and the payout table multiplier for
+    // `roll` in basis points, or 0 when paid on the house edge curve.
+    pub house_edge: u16,
+    pub multiplier: u32,
+    // Premium paid into the insurance fund, and the bps of the stake it
+    // refunds on a loss. Both 0 when uninsured.
+    pub premium: u64,
+    pub insurance_refund: u16,
+    // From this slot on the bet can only be refunded, and house signatures
+    // over it are rejected
+    pub expires_at_slot: u64,
+    // Paid into the player's Claim rather than directly
+    pub claim_payout: bool,
+    // Addressed by the player and a u64 seed, see seed_prefix
+    pub compact_seed: bool
+}
+
+impl Bet {
+    pub const LEN: usize = 8 + 32 + 16 + 8 + 8 + 1 + 1 + 1 + 2 + 8 + 2 + 4 + 8 + 2 + 8 + 1 + 1;
+    pub const MIN_ROLL: u8 = 2;
+    pub const MAX_ROLL: u8 = 96;
+    // Every side bet has to be settled in the resolving transaction, so keep
+    // them few enough to fit
+    pub const MAX_SIDE_BETS: u16 = 8;
+    // Side bets close this many slots after placement, and no bet can
+    // resolve before then, so nobody can bet on a roll they've already seen
+    // the house signature for
+    pub const SIDE_BET_WINDOW: u64 = 20;
+
+    pub fn is_expired(&self, slot: u64) -> bool {
+        slot >= self.expires_at_slot
+    }
+
+    pub fn side_bets_open(&self, slot: u64) -> bool {
+        slot < self.slot.saturating_add(Self::SIDE_BET_WINDOW)
+    }
+
+    // Bets live at [b"bet", vault, prefix, seed]. The prefix is empty for
+    // u128 seeds, which leaves their addresses as they always were, and the
+    // player for compact u64 seeds, so bots can count seeds up from 0
+    // without running into other players.
+    pub fn prefix_for(compact: bool, player: &Pubkey) -> Vec<u8> {
+        if compact {
+            player.to_bytes().to_vec()
+        } else {
+            Vec::new()
+        }
+    }
+
+    pub fn seed_bytes_for(compact: bool, seed: u128) -> Vec<u8> {
+        if compact {
+            (seed as u64).to_le_bytes().to_vec()
+        } else {
+            seed.to_le_bytes().to_vec()
+        }
+    }
+
+    pub fn seed_prefix(&self) -> Vec<u8> {
+        Self::prefix_for(self.compact_seed, &self.player)
+    }
+
+    pub fn seed_bytes(&self) -> Vec<u8> {
+        Self::seed_bytes_for(self.compact_seed, self.seed)
+    }
+}
\ No newline at end of file
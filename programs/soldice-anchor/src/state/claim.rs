@@ -0,0 +1,13 @@
+use anchor_lang::prelude::*;
+
+#[account]
+pub struct Claim {
+    pub player: Pubkey,
+    // Lamports the vault owes the player across every bet resolved in claim mode
+    pub owed: u64,
+    pub bump: u8
+}
+
+impl Claim {
+    pub const LEN: usize = 8 + 32 + 8 + 1;
+}
@@ -0,0 +1,305 @@
+use anchor_lang::prelude::*;
+
+use crate::{state::{Bet, PayoutTable}, contexts::REFUND_TIMEOUT, errors::DiceError, events::{ConfigChanged, ConfigTerms, DailyReport, CONFIG_CHANGED_VERSION, DAILY_REPORT_VERSION}};
+
+#[account]
+#[derive(Default)]
+pub struct Config {
+    pub house: Pubkey,
+    pub min_bankroll: u64,
+    // Table closes for `cooldown` slots once the house loses more than
+    // `loss_limit` lamports within `loss_window` slots. 0 disables it.
+    pub loss_limit: u64,
+    pub loss_window: u64,
+    pub cooldown: u64,
+    pub window_start: u64,
+    pub window_losses: u64,
+    pub paused_until: u64,
+    // Slots after placement that a bet can still be resolved, see
+    // Bet::expires_at_slot. 0 leaves it at REFUND_TIMEOUT.
+    pub expiry_slots: u64,
+    // Set while a PayoutTable is open, in which case place_bet must use it
+    pub payout_table: bool,
+    // Running totals for the UTC day of the last resolution. Only
+    // resolutions count, refunded stakes were never wagered.
+    pub day: i64,
+    pub day_wagered: u64,
+    pub day_paid: u64,
+    // Bet seeds must come from the player's PlayerNonce
+    pub strict_nonce: bool,
+    // resolve_bet ignores its signature argument and rolls with the one the
+    // precompile verified
+    pub strict_signatures: bool,
+    // Bets placed while set are credited to the player's Claim instead of
+    // paid out, and the player withdraws with claim_winnings
+    pub claim_payouts: bool,
+    // In basis points
+    pub house_edge: u16,
+    // Sequence number of the next ConfigChanged event
+    pub change_seq: u64,
+    // Insured bets pay `insurance_premium` bps of the stake into the
+    // insurance fund and get `insurance_refund` bps back on a loss. A 0
+    // premium turns insurance off.
+    pub insurance_premium: u16,
+    pub insurance_refund: u16,
+    // Bps of the stake paid to a relayer that sponsors place_bet
+    pub relayer_surcharge: u16,
+    // New bets use compact u64 seeds, see Bet::prefix_for
+    pub compact_seeds: bool,
+    // Seed after the house key in the config, vault, round, payout table and
+    // insurance addresses, so one house can run several isolated tables.
+    // Empty adds nothing to the seeds, so that table keeps the addresses of
+    // a house with no namespace.
+    pub namespace: Vec<u8>,
+    pub bump: u8
+}
+
+impl Config {
+    pub const LEN: usize = 8 + 32 + 8 + 8 + 8 + 8 + 8 + 8 + 8 + 8 + 1 + 8 + 8 + 8 + 1 + 1 + 1 + 1 + 2 + 8 + 2 + 2 + 2 + (4 + Self::MAX_NAMESPACE_LEN) + 1;
+
+    pub const MAX_HOUSE_EDGE: u16 = 1000;
+    pub const MAX_RELAYER_SURCHARGE: u16 = 500;
+    // The most a PDA seed can hold, so initialize can't derive addresses
+    // for a longer namespace
+    pub const MAX_NAMESPACE_LEN: usize = 32;
+
+    // Every instruction that writes config must call this before returning,
+    // then emit the result of record_change. Pass the payout table when
+    // writing it too, so its rules are checked in the same place.
+    pub fn validate(&self, payout_table: Option<&PayoutTable>) -> Result<()> {
+        require!(self.house_edge <= Self::MAX_HOUSE_EDGE, DiceError::InvalidHouseEdge);
+        if self.loss_limit != 0 {
+            require_neq!(self.loss_window, 0, DiceError::InvalidLossWindow);
+            require_neq!(self.cooldown, 0, DiceError::InvalidCooldown);
+        }
+        require!(self.insurance_premium <= 10_000 && self.insurance_refund <= 10_000, DiceError::InvalidInsurance);
+        // A premium that buys nothing back would just be a second house edge
+        if self.insurance_premium != 0 {
+            require_neq!(self.insurance_refund, 0, DiceError::InvalidInsurance);
+        }
+        // Refunds open at the expiry, so it can't hold them past the refund
+        // timeout, and a bet that expires before its side bets close could
+        // never resolve
+        if self.expiry_slots != 0 {
+            require!(self.expiry_slots > Bet::SIDE_BET_WINDOW && self.expiry_slots <= REFUND_TIMEOUT, DiceError::InvalidExpiry);
+        }
+        require!(self.relayer_surcharge <= Self::MAX_RELAYER_SURCHARGE, DiceError::InvalidRelayerSurcharge);
+        if let Some(table) = payout_table {
+            validate_multipliers(&table.multipliers)?;
+        }
+        Ok(())
+    }
+
+    pub fn terms(&self) -> ConfigTerms {
+        ConfigTerms {
+            house_edge: self.house_edge,
+            min_bankroll: self.min_bankroll,
+            loss_limit: self.loss_limit,
+            loss_window: self.loss_window,
+            cooldown: self.cooldown,
+            expiry_slots: self.expiry_slots,
+            payout_table: self.payout_table,
+            strict_nonce: self.strict_nonce,
+            strict_signatures: self.strict_signatures,
+            claim_payouts: self.claim_payouts,
+            insurance_premium: self.insurance_premium,
+            insurance_refund: self.insurance_refund,
+            relayer_surcharge: self.relayer_surcharge,
+            compact_seeds: self.compact_seeds
+        }
+    }
+
+    pub fn record_change(&mut self, old: ConfigTerms, clock: &Clock) -> ConfigChanged {
+        let change = ConfigChanged {
+            version: CONFIG_CHANGED_VERSION,
+            house: self.house,
+            sequence: self.change_seq,
+            slot: clock.slot,
+            timestamp: clock.unix_timestamp,
+            old,
+            new: self.terms()
+        };
+        self.change_seq = self.change_seq.saturating_add(1);
+        change
+    }
+
+    pub fn is_paused(&self, slot: u64) -> bool {
+        slot < self.paused_until
+    }
+
+    pub fn record_result(&mut self, slot: u64, house_loss: u64, house_win: u64) {
+        if self.loss_limit == 0 {
+            return;
+        }
+
+        // Start a fresh window once the current one has elapsed
+        if slot.saturating_sub(self.window_start) >= self.loss_window {
+            self.window_start = slot;
+            self.window_losses = 0;
+        }
+
+        self.window_losses = self.window_losses
+            .saturating_add(house_loss)
+            .saturating_sub(house_win);
+
+        if self.window_losses > self.loss_limit {
+            self.paused_until = slot.saturating_add(self.cooldown);
+            self.window_start = slot;
+            self.window_losses = 0;
+        }
+    }
+
+    // Returns the previous day's report when `day` starts a new one
+    pub fn record_volume(&mut self, day: i64, wagered: u64, paid: u64) -> Option<DailyReport> {
+        let mut report = None;
+        if day != self.day {
+            if self.day_wagered != 0 || self.day_paid != 0 {
+                report = Some(DailyReport {
+                    version: DAILY_REPORT_VERSION,
+                    house: self.house,
+                    day: self.day,
+                    wagered: self.day_wagered,
+                    paid: self.day_paid,
+                    house_net: self.day_wagered as i128 - self.day_paid as i128
+                });
+            }
+            self.day = day;
+            self.day_wagered = 0;
+            self.day_paid = 0;
+        }
+        self.day_wagered = self.day_wagered.saturating_add(wagered);
+        self.day_paid = self.day_paid.saturating_add(paid);
+        report
+    }
+}
+
+// Each win must pay more than the stake, pay less the likelier it is, and
+// return no more than a fair payout
+fn validate_multipliers(multipliers: &[u32; 95]) -> Result<()> {
+    let mut previous = u32::MAX;
+    for (i, multiplier) in multipliers.iter().enumerate() {
+        // A bet on `roll` wins (roll - 1)% of the time
+        let odds = (Bet::MIN_ROLL as u64 - 1) + i as u64;
+        require!(*multiplier <= previous, DiceError::PayoutCurve);
+        require!(*multiplier > 10_000, DiceError::PayoutMultiplier);
+        require!((*multiplier as u64) * odds <= 10_000 * 100, DiceError::PayoutEdge);
+        previous = *multiplier;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn accumulates_within_a_day() {
+        let mut config = Config { day: 19_000, ..Default::default() };
+        assert!(config.record_volume(19_000, 100, 0).is_none());
+        assert!(config.record_volume(19_000, 50, 190).is_none());
+        assert_eq!((config.day_wagered, config.day_paid), (150, 190));
+    }
+
+    #[test]
+    fn reports_the_previous_day_on_rollover() {
+        let mut config = Config { day: 19_000, ..Default::default() };
+        config.record_volume(19_000, 300, 0);
+        config.record_volume(19_000, 100, 197);
+        let report = config.record_volume(19_002, 40, 0).unwrap();
+        assert_eq!(report.version, DAILY_REPORT_VERSION);
+        assert_eq!(report.day, 19_000);
+        assert_eq!((report.wagered, report.paid, report.house_net), (400, 197, 203));
+        // The new day starts from the resolution that rolled it over
+        assert_eq!((config.day, config.day_wagered, config.day_paid), (19_002, 40, 0));
+    }
+
+    #[test]
+    fn reports_a_losing_day() {
+        let mut config = Config { day: 19_000, ..Default::default() };
+        config.record_volume(19_000, 100, 197);
+        let report = config.record_volume(19_001, 0, 0).unwrap();
+        assert_eq!(report.house_net, -97);
+    }
+
+    fn fair_table() -> PayoutTable {
+        let mut multipliers = [0u32; 95];
+        for (i, multiplier) in multipliers.iter_mut().enumerate() {
+            *multiplier = 990_000 / (i as u32 + 1);
+        }
+        PayoutTable { house: Pubkey::default(), multipliers, bump: 0 }
+    }
+
+    fn rejects(config: Config, payout_table: Option<&PayoutTable>, error: DiceError) {
+        assert_eq!(config.validate(payout_table), Err(error.into()));
+    }
+
+    #[test]
+    fn accepts_the_defaults_and_a_fair_table() {
+        assert_eq!(Config::default().validate(None), Ok(()));
+        assert_eq!(Config::default().validate(Some(&fair_table())), Ok(()));
+    }
+
+    #[test]
+    fn rejects_a_house_edge_above_the_maximum() {
+        rejects(Config { house_edge: Config::MAX_HOUSE_EDGE + 1, ..Default::default() }, None, DiceError::InvalidHouseEdge);
+    }
+
+    #[test]
+    fn rejects_a_loss_limit_without_a_window_or_cooldown() {
+        rejects(Config { loss_limit: 1, cooldown: 1, ..Default::default() }, None, DiceError::InvalidLossWindow);
+        rejects(Config { loss_limit: 1, loss_window: 1, ..Default::default() }, None, DiceError::InvalidCooldown);
+    }
+
+    #[test]
+    fn rejects_insurance_out_of_range() {
+        rejects(Config { insurance_premium: 10_001, insurance_refund: 100, ..Default::default() }, None, DiceError::InvalidInsurance);
+        rejects(Config { insurance_premium: 100, insurance_refund: 10_001, ..Default::default() }, None, DiceError::InvalidInsurance);
+        rejects(Config { insurance_premium: 100, ..Default::default() }, None, DiceError::InvalidInsurance);
+    }
+
+    #[test]
+    fn bounds_the_expiry() {
+        rejects(Config { expiry_slots: Bet::SIDE_BET_WINDOW, ..Default::default() }, None, DiceError::InvalidExpiry);
+        rejects(Config { expiry_slots: REFUND_TIMEOUT + 1, ..Default::default() }, None, DiceError::InvalidExpiry);
+        for expiry_slots in [Bet::SIDE_BET_WINDOW + 1, REFUND_TIMEOUT] {
+            assert_eq!(Config { expiry_slots, ..Default::default() }.validate(None), Ok(()));
+        }
+    }
+
+    #[test]
+    fn rejects_a_relayer_surcharge_above_the_maximum() {
+        rejects(Config { relayer_surcharge: Config::MAX_RELAYER_SURCHARGE + 1, ..Default::default() }, None, DiceError::InvalidRelayerSurcharge);
+    }
+
+    #[test]
+    fn rejects_a_rising_payout_curve() {
+        let mut table = fair_table();
+        table.multipliers.swap(10, 11);
+        rejects(Config::default(), Some(&table), DiceError::PayoutCurve);
+    }
+
+    #[test]
+    fn rejects_a_payout_that_returns_no_more_than_the_stake() {
+        let mut table = fair_table();
+        if let Some(last) = table.multipliers.last_mut() {
+            *last = 10_000;
+        }
+        rejects(Config::default(), Some(&table), DiceError::PayoutMultiplier);
+    }
+
+    #[test]
+    fn rejects_a_payout_above_fair() {
+        let mut table = fair_table();
+        if let Some(first) = table.multipliers.first_mut() {
+            *first = 1_000_001;
+        }
+        rejects(Config::default(), Some(&table), DiceError::PayoutEdge);
+    }
+
+    #[test]
+    fn skips_a_day_without_volume() {
+        let mut config = Config::default();
+        assert!(config.record_volume(19_000, 100, 0).is_none());
+        assert_eq!(config.day, 19_000);
+    }
+}
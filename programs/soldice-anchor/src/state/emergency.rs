@@ -0,0 +1,61 @@
+use anchor_lang::prelude::*;
+
+// Program-wide kill switch. While active, bets can't be placed or resolved
+// anywhere, houses can't change their terms or rounds, and every open bet
+// can be refunded straight away.
+#[account]
+pub struct Emergency {
+    // The program's upgrade authority when the switch was created, until it
+    // hands the switch on with set_emergency_authority
+    pub authority: Pubkey,
+    // Slots between arming or lifting the switch and it taking effect
+    pub timelock: u64,
+    // Active for slots in [active_from, active_until)
+    pub active_from: u64,
+    pub active_until: u64,
+    pub bump: u8
+}
+
+impl Emergency {
+    pub const LEN: usize = 8 + 32 + 8 + 8 + 8 + 1;
+
+    // About a minute, so players see the switch coming before it takes effect
+    pub const MIN_TIMELOCK: u64 = 150;
+
+    pub fn is_active(&self, slot: u64) -> bool {
+        slot >= self.active_from && slot < self.active_until
+    }
+
+    // For the emergency PDA, which stays empty until init_emergency runs and
+    // reads as inactive until then
+    pub fn is_active_at(info: &AccountInfo, slot: u64) -> Result<bool> {
+        if info.owner != &crate::ID || info.data_is_empty() {
+            return Ok(false);
+        }
+        let emergency = Self::try_deserialize(&mut &info.try_borrow_data()?[..])?;
+        Ok(emergency.is_active(slot))
+    }
+
+    pub fn arm(&mut self, slot: u64) {
+        // Arming while active just cancels any pending lift, and arming while
+        // already armed keeps the original activation slot
+        if !self.is_active(slot) && !self.is_pending(slot) {
+            self.active_from = slot.saturating_add(self.timelock);
+        }
+        self.active_until = u64::MAX;
+    }
+
+    // Armed, but still inside the timelock
+    fn is_pending(&self, slot: u64) -> bool {
+        slot < self.active_from && self.active_from != u64::MAX
+    }
+
+    pub fn lift(&mut self, slot: u64) {
+        if self.is_active(slot) {
+            self.active_until = slot.saturating_add(self.timelock);
+        } else {
+            self.active_from = u64::MAX;
+            self.active_until = u64::MAX;
+        }
+    }
+}
@@ -1,24 +1,23 @@
-use anchor_lang::prelude::*;
+pub mod bet;
+pub use bet::*;
 
-#[account]
-pub struct Bet {
-    pub player: Pubkey,
-    pub seed: u128,
-    pub slot: u64,
-    pub amount: u64,
-    pub roll: u8,
-    pub bump : u8
-}
+pub mod config;
+pub use config::*;
 
-impl Bet {
-    pub const LEN: usize = 8 + 32 + 16 + 8 + 8 + 1 + 1;
+pub mod payout_table;
+pub use payout_table::*;
 
-    pub fn to_slice(&self) -> Vec<u8> {
-        let mut s = self.player.to_bytes().to_vec();
-        s.extend_from_slice(&self.seed.to_le_bytes());
-        s.extend_from_slice(&self.slot.to_le_bytes());
-        s.extend_from_slice(&self.amount.to_le_bytes());
-        s.extend_from_slice(&[self.roll, self.bump]);
-        s        
-    }
-}
\ No newline at end of file
+pub mod side_bet;
+pub use side_bet::*;
+
+pub mod round;
+pub use round::*;
+
+pub mod player_nonce;
+pub use player_nonce::*;
+
+pub mod claim;
+pub use claim::*;
+
+pub mod emergency;
+pub use emergency::*;
@@ -0,0 +1,26 @@
+use anchor_lang::prelude::*;
+
+use crate::{state::Bet, errors::DiceError};
+
+pub const PAYOUT_TABLE_ROLLS: usize = (Bet::MAX_ROLL - Bet::MIN_ROLL + 1) as usize;
+
+// The IDL needs a literal array length, so keep it in step with the roll range
+const _: () = assert!(PAYOUT_TABLE_ROLLS == 95);
+
+#[account]
+pub struct PayoutTable {
+    pub house: Pubkey,
+    // Multiplier in basis points for each roll, starting at Bet::MIN_ROLL
+    pub multipliers: [u32; 95],
+    pub bump: u8
+}
+
+impl PayoutTable {
+    pub const LEN: usize = 8 + 32 + 4 * PAYOUT_TABLE_ROLLS + 1;
+
+    pub fn multiplier(&self, roll: u8) -> Result<u32> {
+        let index = roll.checked_sub(Bet::MIN_ROLL).ok_or(DiceError::MinimumRoll)? as usize;
+        let multiplier = *self.multipliers.get(index).ok_or(DiceError::MaximumRoll)?;
+        Ok(multiplier)
+    }
+}
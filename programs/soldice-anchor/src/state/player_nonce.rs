@@ -0,0 +1,23 @@
+use anchor_lang::prelude::*;
+
+#[account]
+pub struct PlayerNonce {
+    pub player: Pubkey,
+    pub nonce: u64,
+    pub bump: u8
+}
+
+impl PlayerNonce {
+    pub const LEN: usize = 8 + 32 + 8 + 1;
+
+    // u128 bet seeds aren't keyed by player, so the player's key prefix keeps
+    // equal nonces from different players apart. Compact seeds already are.
+    pub fn next_seed(&self, compact: bool) -> u128 {
+        if compact {
+            return self.nonce as u128;
+        }
+        let mut prefix: [u8;8] = [0;8];
+        prefix.iter_mut().zip(self.player.to_bytes()).for_each(|(dst, src)| *dst = src);
+        ((u64::from_le_bytes(prefix) as u128) << 64) | self.nonce as u128
+    }
+}
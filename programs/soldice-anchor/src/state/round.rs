@@ -0,0 +1,73 @@
+use anchor_lang::prelude::*;
+
+use crate::errors::DiceError;
+
+pub const MAX_ROUND_ENTRIES: usize = 32;
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+pub struct RoundEntry {
+    pub player: Pubkey,
+    pub wagered: u64,
+    pub won: u64
+}
+
+impl RoundEntry {
+    pub const LEN: usize = 32 + 8 + 8;
+
+    pub fn net(&self) -> i128 {
+        self.won as i128 - self.wagered as i128
+    }
+}
+
+#[account]
+pub struct Round {
+    pub house: Pubkey,
+    pub round_id: u64,
+    pub open_bets: u16,
+    pub finalized: bool,
+    // Ranked by net winnings once finalized
+    pub entries: Vec<RoundEntry>,
+    pub bump: u8
+}
+
+impl Round {
+    pub const LEN: usize = 8 + 32 + 8 + 2 + 1 + 4 + MAX_ROUND_ENTRIES * RoundEntry::LEN + 1;
+
+    // Only the house enters players, so nobody can fill the round with
+    // entries of their own
+    pub fn enter(&mut self, player: Pubkey) -> Result<()> {
+        require!(!self.finalized, DiceError::RoundFinalized);
+        if !self.entries.iter().any(|e| e.player == player) {
+            require!(self.entries.len() < MAX_ROUND_ENTRIES, DiceError::RoundFull);
+            self.entries.push(RoundEntry {
+                player,
+                wagered: 0,
+                won: 0
+            });
+        }
+        Ok(())
+    }
+
+    pub fn join(&mut self, player: Pubkey) -> Result<()> {
+        require!(!self.finalized, DiceError::RoundFinalized);
+        require!(self.entries.iter().any(|e| e.player == player), DiceError::NotInRound);
+        self.open_bets = self.open_bets.checked_add(1).ok_or(DiceError::Overflow)?;
+        Ok(())
+    }
+
+    pub fn settle(&mut self, player: Pubkey, wagered: u64, won: u64) -> Result<()> {
+        let entry = self.entries.iter_mut().find(|e| e.player == player).ok_or(DiceError::RoundMismatch)?;
+        entry.wagered = entry.wagered.checked_add(wagered).ok_or(DiceError::Overflow)?;
+        entry.won = entry.won.checked_add(won).ok_or(DiceError::Overflow)?;
+        self.open_bets = self.open_bets.checked_sub(1).ok_or(DiceError::Overflow)?;
+        Ok(())
+    }
+
+    pub fn finalize(&mut self) -> Result<()> {
+        require!(!self.finalized, DiceError::RoundFinalized);
+        require_eq!(self.open_bets, 0, DiceError::RoundOpenBets);
+        self.entries.sort_by(|a, b| b.net().cmp(&a.net()));
+        self.finalized = true;
+        Ok(())
+    }
+}
@@ -0,0 +1,16 @@
+use anchor_lang::prelude::*;
+
+#[account]
+pub struct SideBet {
+    pub bettor: Pubkey,
+    pub bet: Pubkey,
+    pub amount: u64,
+    // Backs the player to win their roll, otherwise to lose it
+    pub on_win: bool,
+    pub bump: u8
+}
+
+impl SideBet {
+    pub const LEN: usize = 8 + 32 + 32 + 8 + 1 + 1;
+    pub const MIN_AMOUNT: u64 = 10_000_000;
+}
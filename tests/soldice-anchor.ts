@@ -1,9 +1,11 @@
 import * as anchor from "@coral-xyz/anchor";
 import { Program } from "@coral-xyz/anchor";
-import { Transaction, Ed25519Program, Keypair, PublicKey, SystemProgram, LAMPORTS_PER_SOL, SYSVAR_INSTRUCTIONS_PUBKEY, sendAndConfirmTransaction } from "@solana/web3.js";
+import { Transaction, TransactionInstruction, Ed25519Program, ComputeBudgetProgram, Keypair, PublicKey, SystemProgram, LAMPORTS_PER_SOL, SYSVAR_INSTRUCTIONS_PUBKEY, sendAndConfirmTransaction } from "@solana/web3.js";
 import { SoldiceAnchor, IDL } from "../target/types/soldice_anchor";
-import { randomBytes } from "crypto"
+import { randomBytes, createHash } from "crypto"
 import { BN } from "bn.js";
+import { assert } from "chai";
+import { existsSync, readFileSync, writeFileSync } from "fs";
 
 describe("soldice-anchor", () => {
   // Configure the client to use the local cluster.
@@ -14,29 +16,341 @@ describe("soldice-anchor", () => {
   const MSG = Uint8Array.from(Buffer.from("1337", "hex"));
   let house = new Keypair();
   let player = new Keypair();
+  let attacker = new Keypair();
+  let bettor = new Keypair();
+  let relayer = new Keypair();
+  let otherHouse = new Keypair();
+  let backer = new Keypair();
   let seed = new BN(randomBytes(16));
   let vault = PublicKey.findProgramAddressSync([Buffer.from("vault"), house.publicKey.toBuffer()], program.programId)[0];
+  let config = PublicKey.findProgramAddressSync([Buffer.from("config"), house.publicKey.toBuffer()], program.programId)[0];
   let bet = PublicKey.findProgramAddressSync([Buffer.from("bet"), vault.toBuffer(), seed.toBuffer("le", 16)], program.programId)[0];
+  let emergency = PublicKey.findProgramAddressSync([Buffer.from("emergency")], program.programId)[0];
+  let payoutTable = PublicKey.findProgramAddressSync([Buffer.from("payouts"), house.publicKey.toBuffer()], program.programId)[0];
   let signature: Uint8Array;
+  let usage: Usage[] = [];
 
   it("Airdrop", async () => {
-    await Promise.all([house, player].map(async (k) => {
+    await Promise.all([house, player, bettor, relayer, otherHouse, backer].map(async (k) => {
       return await anchor.getProvider().connection.requestAirdrop(k.publicKey, 1000 * anchor.web3.LAMPORTS_PER_SOL).then(confirmTx)
     }));
   });
 
   it("Initialize", async () => {
     // Add your test here.
-    let signature = await program.methods.initialize(new BN(LAMPORTS_PER_SOL).mul(new BN(100)))
+    let signature = await program.methods.initialize(new BN(LAMPORTS_PER_SOL).mul(new BN(100)), new BN(LAMPORTS_PER_SOL).mul(new BN(10)), Buffer.alloc(0))
     .accounts({
       house: house.publicKey,
       vault,
+      config,
       systemProgram:SystemProgram.programId 
     })
     .signers([
       house
     ])
     .rpc().then(confirmTx);
+    usage.push(await measure("initialize", signature));
+  });
+
+  it("Derive addresses", async () => {
+    let addresses = await program.methods.deriveAddresses(player.publicKey, seed)
+    .accounts({
+      house: house.publicKey,
+      config
+    })
+    .view();
+    assert.isTrue(addresses.vault.equals(vault));
+    assert.isTrue(addresses.config.equals(config));
+    assert.isTrue(addresses.bet.equals(bet));
+  });
+
+  it("Keeps a namespaced table apart from the house's default one", async () => {
+    let namespace = Buffer.from("brand");
+    let [brandVault, brandConfig] = ["vault", "config"].map((prefix) => PublicKey.findProgramAddressSync([Buffer.from(prefix), house.publicKey.toBuffer(), namespace], program.programId)[0]);
+    await program.methods.initialize(new BN(LAMPORTS_PER_SOL), new BN(0), namespace)
+    .accounts({
+      house: house.publicKey,
+      vault: brandVault,
+      config: brandConfig,
+      systemProgram: SystemProgram.programId
+    })
+    .signers([
+      house
+    ])
+    .rpc().then(confirmTx);
+    assert.isTrue(Buffer.from((await program.account.config.fetch(brandConfig, "confirmed")).namespace).equals(namespace));
+
+    // The same player and seed land on a different bet than on the default table
+    let brandBet = PublicKey.findProgramAddressSync([Buffer.from("bet"), brandVault.toBuffer(), seed.toBuffer("le", 16)], program.programId)[0];
+    assert.isFalse(brandBet.equals(bet));
+    let addresses = await program.methods.deriveAddresses(player.publicKey, seed)
+    .accounts({
+      house: house.publicKey,
+      config: brandConfig
+    })
+    .view();
+    assert.isTrue(addresses.vault.equals(brandVault));
+    assert.isTrue(addresses.config.equals(brandConfig));
+    assert.isTrue(addresses.bet.equals(brandBet));
+
+    let before = await anchor.getProvider().connection.getBalance(brandVault, "confirmed");
+    await program.methods.placeBet(seed, 50, new BN(LAMPORTS_PER_SOL/100))
+    .accounts({
+      player: player.publicKey,
+      house: house.publicKey,
+      vault: brandVault,
+      config: brandConfig,
+      bet: brandBet,
+      round: null,
+      nonce: null,
+      claim: null,
+      payoutTable: null,
+      insurance: null,
+      relayer: null,
+      emergency,
+      systemProgram: SystemProgram.programId
+    })
+    .signers([
+      player
+    ])
+    .rpc().then(confirmTx);
+    assert.equal(await anchor.getProvider().connection.getBalance(brandVault, "confirmed") - before, LAMPORTS_PER_SOL/100);
+  });
+
+  it("Takes bets before the emergency switch exists", async () => {
+    let early = new BN(randomBytes(16));
+    let earlyBet = PublicKey.findProgramAddressSync([Buffer.from("bet"), vault.toBuffer(), early.toBuffer("le", 16)], program.programId)[0];
+    await program.methods.placeBet(early, 50, new BN(LAMPORTS_PER_SOL/100))
+    .accounts({
+      player: player.publicKey,
+      house: house.publicKey,
+      vault,
+      config,
+      bet: earlyBet,
+      round: null,
+      nonce: null,
+      claim: null,
+      payoutTable: null,
+      insurance: null,
+      relayer: null,
+      emergency,
+      systemProgram: SystemProgram.programId
+    })
+    .signers([
+      player
+    ])
+    .rpc().then(confirmTx);
+    assert.isNotNull(await anchor.getProvider().connection.getAccountInfo(earlyBet, "confirmed"));
+  });
+
+  it("Initialize the emergency switch", async () => {
+    let programData = PublicKey.findProgramAddressSync([program.programId.toBuffer()], new PublicKey("BPFLoaderUpgradeab1e11111111111111111111111"))[0];
+    let initEmergency = (timelock: number) => program.methods.initEmergency(new BN(timelock))
+    .accounts({
+      authority: program.provider.publicKey,
+      program: program.programId,
+      programData,
+      emergency,
+      systemProgram: SystemProgram.programId
+    })
+    .rpc();
+
+    let rejected = false;
+    try {
+      await initEmergency(0);
+    } catch (e) {
+      assert.equal(e.error.errorCode.code, "InvalidTimelock");
+      rejected = true;
+    }
+    assert.isTrue(rejected, "expected InvalidTimelock");
+
+    await initEmergency(150).then(confirmTx);
+  });
+
+  it("Rotates the emergency authority", async () => {
+    let rotate = (authority: Keypair | null, newAuthority: Keypair | null) => program.methods.setEmergencyAuthority()
+    .accounts({
+      authority: authority ? authority.publicKey : program.provider.publicKey,
+      newAuthority: newAuthority ? newAuthority.publicKey : program.provider.publicKey,
+      emergency
+    })
+    .signers([authority, newAuthority].filter((k) => k))
+    .rpc();
+
+    await rotate(null, backer).then(confirmTx);
+    assert.isTrue((await program.account.emergency.fetch(emergency, "confirmed")).authority.equals(backer.publicKey));
+
+    // The old authority can no longer work the switch
+    let rejected = false;
+    try {
+      await program.methods.setEmergency(true)
+      .accounts({
+        authority: program.provider.publicKey,
+        emergency
+      })
+      .rpc();
+    } catch (e) {
+      assert.equal(e.error.errorCode.code, "ConstraintHasOne");
+      rejected = true;
+    }
+    assert.isTrue(rejected, "expected ConstraintHasOne");
+
+    await rotate(backer, null).then(confirmTx);
+    assert.isTrue((await program.account.emergency.fetch(emergency, "confirmed")).authority.equals(program.provider.publicKey));
+  });
+
+  it("Verify resolver", async () => {
+    let sig_ix = Ed25519Program.createInstructionWithPrivateKey({
+      privateKey: house.secretKey,
+      message: Buffer.concat([Buffer.from("soldice:verify_resolver"), house.publicKey.toBuffer()])
+    });
+
+    const verify_ix = await program.methods.verifyResolver().accounts({
+      house: house.publicKey,
+      config,
+      instructionSysvar: SYSVAR_INSTRUCTIONS_PUBKEY
+    })
+    .instruction();
+
+    await sendAndConfirmTransaction(
+      program.provider.connection,
+      new Transaction().add(sig_ix).add(verify_ix),
+      [house]
+    );
+  });
+
+  it("Rejects a loss limit without a window", async () => {
+    try {
+      await program.methods.setLossLimit(new BN(LAMPORTS_PER_SOL), new BN(0), new BN(100))
+      .accounts({
+        house: house.publicKey,
+        config,
+        emergency
+      })
+      .signers([
+        house
+      ])
+      .rpc();
+    } catch (e) {
+      assert.equal(e.error.errorCode.code, "InvalidLossWindow");
+      return;
+    }
+    assert.fail("expected InvalidLossWindow");
+  });
+
+  it("Rejects a loss limit without a cooldown", async () => {
+    try {
+      await program.methods.setLossLimit(new BN(LAMPORTS_PER_SOL), new BN(1000), new BN(0))
+      .accounts({
+        house: house.publicKey,
+        config,
+        emergency
+      })
+      .signers([
+        house
+      ])
+      .rpc();
+    } catch (e) {
+      assert.equal(e.error.errorCode.code, "InvalidCooldown");
+      return;
+    }
+    assert.fail("expected InvalidCooldown");
+  });
+
+  it("Rejects config outside its valid ranges", async () => {
+    let cases: { name: string, code: string, update: () => any }[] = [
+      { name: "expiry past the refund timeout", code: "InvalidExpiry", update: () => program.methods.setExpiry(new BN(1001)) },
+      { name: "expiry inside the side bet window", code: "InvalidExpiry", update: () => program.methods.setExpiry(new BN(20)) },
+      { name: "an insurance premium above 100%", code: "InvalidInsurance", update: () => program.methods.setInsurance(10001, 100) },
+      { name: "an insurance refund above 100%", code: "InvalidInsurance", update: () => program.methods.setInsurance(100, 10001) },
+      { name: "a relayer surcharge above the maximum", code: "InvalidRelayerSurcharge", update: () => program.methods.setRelayerSurcharge(501) }
+    ];
+    for (const c of cases) {
+      try {
+        await c.update()
+        .accounts({
+          house: house.publicKey,
+          config,
+          emergency
+        })
+        .signers([
+          house
+        ])
+        .rpc();
+      } catch (e) {
+        assert.equal(e.error.errorCode.code, c.code, c.name);
+        continue;
+      }
+      assert.fail(`expected ${c.code} for ${c.name}`);
+    }
+  });
+
+  it("Rejects a house edge above the maximum", async () => {
+    try {
+      await program.methods.setHouseEdge(1001)
+      .accounts({
+        house: house.publicKey,
+        config,
+        emergency
+      })
+      .signers([
+        house
+      ])
+      .rpc();
+    } catch (e) {
+      assert.equal(e.error.errorCode.code, "InvalidHouseEdge");
+      return;
+    }
+    assert.fail("expected InvalidHouseEdge");
+  });
+
+  it("Rejects an insurance premium with no refund", async () => {
+    try {
+      await program.methods.setInsurance(100, 0)
+      .accounts({
+        house: house.publicKey,
+        config,
+        emergency
+      })
+      .signers([
+        house
+      ])
+      .rpc();
+    } catch (e) {
+      assert.equal(e.error.errorCode.code, "InvalidInsurance");
+      return;
+    }
+    assert.fail("expected InvalidInsurance");
+  });
+
+  it("Rejects insurance when it isn't offered", async () => {
+    let fake = new BN(randomBytes(16));
+    try {
+      await program.methods.placeBet(fake, 50, new BN(LAMPORTS_PER_SOL/100))
+      .accounts({
+        player: player.publicKey,
+        house: house.publicKey,
+        vault,
+        config,
+        bet: PublicKey.findProgramAddressSync([Buffer.from("bet"), vault.toBuffer(), fake.toBuffer("le", 16)], program.programId)[0],
+        round: null,
+        nonce: null,
+        claim: null,
+        payoutTable: null,
+        insurance: PublicKey.findProgramAddressSync([Buffer.from("insurance"), house.publicKey.toBuffer()], program.programId)[0],
+        relayer: null,
+        emergency,
+        systemProgram: SystemProgram.programId
+      })
+      .signers([
+        player
+      ])
+      .rpc();
+    } catch (e) {
+      assert.equal(e.error.errorCode.code, "InsuranceDisabled");
+      return;
+    }
+    assert.fail("expected InsuranceDisabled");
   });
 
   it("Place a bet", async () => {
@@ -46,27 +360,464 @@ describe("soldice-anchor", () => {
       player: player.publicKey,
       house: house.publicKey,
       vault,
+      config,
       bet,
+      round: null,
+      nonce: null,
+      claim: null,
+      payoutTable: null,
+      insurance: null,
+      relayer: null,
+      emergency,
       systemProgram:SystemProgram.programId 
     })
     .signers([
       player
     ])
     .rpc().then(confirmTx);
+    usage.push(await measure("placeBet", signature));
   });
 
-  it("Resolve a bet", async () => {
+  it("Rejects a side bet below the minimum", async () => {
+    try {
+      await program.methods.placeSideBet(new BN(LAMPORTS_PER_SOL/1000), true)
+      .accounts({
+        bettor: bettor.publicKey,
+        house: house.publicKey,
+        vault,
+        config,
+        bet,
+        sideBet: PublicKey.findProgramAddressSync([Buffer.from("side"), bet.toBuffer(), bettor.publicKey.toBuffer()], program.programId)[0],
+        emergency,
+        systemProgram: SystemProgram.programId
+      })
+      .signers([
+        bettor
+      ])
+      .rpc();
+    } catch (e) {
+      assert.equal(e.error.errorCode.code, "MinimumBet");
+      return;
+    }
+    assert.fail("expected MinimumBet");
+  });
+
+  it("Rejects a resolve paying out to a substituted player", async () => {
+    try {
+      await program.methods.resolveBet(Buffer.alloc(64))
+      .accounts({
+        player: attacker.publicKey,
+        house: house.publicKey,
+        vault,
+        config,
+        bet,
+        round: null,
+        claim: null,
+        memoProgram: null,
+        insurance: null,
+        emergency,
+        instructionSysvar: SYSVAR_INSTRUCTIONS_PUBKEY,
+        systemProgram: SystemProgram.programId
+      })
+      .signers([
+        house
+      ])
+      .rpc();
+    } catch (e) {
+      assert.equal(e.error.errorCode.code, "ConstraintHasOne");
+      return;
+    }
+    assert.fail("expected ConstraintHasOne");
+  });
+
+  it("Rejects a refund of someone else's bet", async () => {
+    try {
+      await program.methods.refundBet()
+      .accounts({
+        player: attacker.publicKey,
+        house: house.publicKey,
+        vault,
+        config,
+        bet,
+        round: null,
+        insurance: null,
+        emergency: null,
+        systemProgram: SystemProgram.programId
+      })
+      .signers([
+        attacker
+      ])
+      .rpc();
+    } catch (e) {
+      assert.equal(e.error.errorCode.code, "ConstraintHasOne");
+      return;
+    }
+    assert.fail("expected ConstraintHasOne");
+  });
+
+  it("Rejects a bet against a program-owned house", async () => {
+    let fake = new BN(randomBytes(16));
+    try {
+      await program.methods.placeBet(fake, 50, new BN(LAMPORTS_PER_SOL/100))
+      .accounts({
+        player: player.publicKey,
+        house: config,
+        vault: PublicKey.findProgramAddressSync([Buffer.from("vault"), config.toBuffer()], program.programId)[0],
+        config,
+        bet: PublicKey.findProgramAddressSync([Buffer.from("bet"), vault.toBuffer(), fake.toBuffer("le", 16)], program.programId)[0],
+        round: null,
+        nonce: null,
+        claim: null,
+        payoutTable: null,
+        insurance: null,
+        relayer: null,
+        emergency,
+        systemProgram: SystemProgram.programId
+      })
+      .signers([
+        player
+      ])
+      .rpc();
+    } catch (e) {
+      assert.equal(e.error.errorCode.code, "AccountNotSystemOwned");
+      return;
+    }
+    assert.fail("expected AccountNotSystemOwned");
+  });
+
+  it("Rejects a bet placed by the house", async () => {
+    let fake = new BN(randomBytes(16));
+    try {
+      await program.methods.placeBet(fake, 50, new BN(LAMPORTS_PER_SOL/100))
+      .accounts({
+        player: house.publicKey,
+        house: house.publicKey,
+        vault,
+        config,
+        bet: PublicKey.findProgramAddressSync([Buffer.from("bet"), vault.toBuffer(), fake.toBuffer("le", 16)], program.programId)[0],
+        round: null,
+        nonce: null,
+        claim: null,
+        payoutTable: null,
+        insurance: null,
+        relayer: null,
+        emergency,
+        systemProgram: SystemProgram.programId
+      })
+      .signers([
+        house
+      ])
+      .rpc();
+    } catch (e) {
+      assert.equal(e.error.errorCode.code, "PlayerIsHouse");
+      return;
+    }
+    assert.fail("expected PlayerIsHouse");
+  });
+
+  it("Rejects a resolve paying out to the vault", async () => {
+    // Fails on the accounts, before the signature is looked at
+    let tx = new Transaction().add(await resolveIx(Buffer.alloc(64), [], { player: vault }));
+    try {
+      await sendAndConfirmTransaction(program.provider.connection, tx, [house]);
+    } catch (e) {
+      assert.equal(anchor.AnchorError.parse(e.logs)?.error.errorCode.code, "PlayerIsVault");
+      return;
+    }
+    assert.fail("expected PlayerIsVault");
+  });
+
+  it("Rejects a nonce for the house", async () => {
+    try {
+      await program.methods.initNonce()
+      .accounts({
+        player: house.publicKey,
+        house: house.publicKey,
+        vault,
+        config,
+        nonce: PublicKey.findProgramAddressSync([Buffer.from("nonce"), vault.toBuffer(), house.publicKey.toBuffer()], program.programId)[0],
+        systemProgram: SystemProgram.programId
+      })
+      .signers([
+        house
+      ])
+      .rpc();
+    } catch (e) {
+      assert.equal(e.error.errorCode.code, "PlayerIsHouse");
+      return;
+    }
+    assert.fail("expected PlayerIsHouse");
+  });
+
+  it("Encodes the canonical bet message", async () => {
     let account = await anchor.getProvider().connection.getAccountInfo(bet, "confirmed");
+    let fetched = await program.account.bet.fetch(bet, "confirmed");
+    let message = betMessage(fetched);
+    let prefix = BET_MESSAGE_DOMAIN.length + 32;
+    assert.equal(message.length, prefix + 76);
+    assert.isTrue(message.subarray(0, prefix).equals(Buffer.concat([BET_MESSAGE_DOMAIN, program.programId.toBuffer()])));
+    assert.equal(message[prefix], BET_MESSAGE_VERSION);
+    // After the version, the message is the Bet fields in declaration order, so it matches the start of the account data
+    assert.isTrue(message.subarray(prefix + 1, prefix + 68).equals(account.data.subarray(8, 8 + 67)));
+    assert.isTrue(message.subarray(prefix + 68).equals(fetched.expiresAtSlot.toArrayLike(Buffer, "le", 8)));
+  });
+
+  it("Rejects corrupted Ed25519 verification", async () => {
+    let message = betMessage(await program.account.bet.fetch(bet, "confirmed"));
+    // So the checks after the side bet window are reached
+    await waitForSideBets(bet);
+    let sign = (privateKey: Uint8Array, message: Uint8Array) => Ed25519Program.createInstructionWithPrivateKey({ privateKey, message });
+    let sigOf = (ix: TransactionInstruction) => Buffer.from(ix.data.subarray(16+32, 16+32+64));
+    let verified = sign(house.secretKey, message);
+    let secondResolve = await resolveIx(sigOf(verified));
+
+    // Each case is accepted by the precompile but must be rejected by resolve_bet
+    let cases: { name: string, code: string, build: () => { ixs: TransactionInstruction[], sig: Buffer, remaining?: PublicKey[], after?: TransactionInstruction[] } }[] = [
+      {
+        name: "signed by another key",
+        code: "Ed25519Pubkey",
+        build: () => {
+          let ix = sign(attacker.secretKey, message);
+          return { ixs: [ix], sig: sigOf(ix) };
+        }
+      },
+      {
+        name: "signed over another message",
+        code: "Ed25519Message",
+        build: () => {
+          let other = Buffer.from(message);
+          other[other.length - 1] ^= 0xff;
+          let ix = sign(house.secretKey, other);
+          return { ixs: [ix], sig: sigOf(ix) };
+        }
+      },
+      {
+        name: "signature argument differs from the verified one",
+        code: "Ed25519Signature",
+        build: () => {
+          let ix = sign(house.secretKey, message);
+          let sig = sigOf(ix);
+          sig[0] ^= 0xff;
+          return { ixs: [ix], sig };
+        }
+      },
+      {
+        name: "truncated signature argument",
+        code: "Ed25519Signature",
+        build: () => {
+          let ix = sign(house.secretKey, message);
+          return { ixs: [ix], sig: sigOf(ix).subarray(0, 32) };
+        }
+      },
+      {
+        name: "odd number of side bet accounts",
+        code: "SideBetAccounts",
+        build: () => {
+          let ix = sign(house.secretKey, message);
+          return { ixs: [ix], sig: sigOf(ix), remaining: [player.publicKey] };
+        }
+      },
+      {
+        name: "verification not immediately before resolve",
+        code: "Ed25519Program",
+        build: () => {
+          let ix = sign(house.secretKey, message);
+          return { ixs: [ix, ComputeBudgetProgram.setComputeUnitLimit({ units: 400_000 })], sig: sigOf(ix) };
+        }
+      },
+      {
+        name: "extra Ed25519 instruction in the transaction",
+        code: "Ed25519Instructions",
+        build: () => {
+          let ix = sign(house.secretKey, message);
+          return { ixs: [sign(attacker.secretKey, message), ix], sig: sigOf(ix) };
+        }
+      },
+      {
+        name: "extra Ed25519 instruction after the resolve",
+        code: "Ed25519Instructions",
+        build: () => ({ ixs: [verified], sig: sigOf(verified), after: [sign(attacker.secretKey, message)] })
+      },
+      {
+        name: "second resolve_bet in the transaction",
+        code: "MultipleResolutions",
+        build: () => ({ ixs: [verified], sig: sigOf(verified), after: [verified, secondResolve] })
+      },
+      {
+        name: "verification with accounts attached",
+        code: "Ed25519Accounts",
+        build: () => {
+          let ix = sign(house.secretKey, message);
+          ix.keys.push({ pubkey: house.publicKey, isSigner: false, isWritable: false });
+          return { ixs: [ix], sig: sigOf(ix) };
+        }
+      },
+      {
+        name: "verification with a non-canonical layout",
+        code: "Ed25519Layout",
+        build: () => {
+          // Same signature, but with the message placed before it
+          let sig = sigOf(sign(house.secretKey, message));
+          let data = Buffer.alloc(16 + 32 + message.length + 64);
+          data.writeUInt8(1, 0);
+          data.writeUInt16LE(16 + 32 + message.length, 2);
+          data.writeUInt16LE(0xffff, 4);
+          data.writeUInt16LE(16, 6);
+          data.writeUInt16LE(0xffff, 8);
+          data.writeUInt16LE(16 + 32, 10);
+          data.writeUInt16LE(message.length, 12);
+          data.writeUInt16LE(0xffff, 14);
+          house.publicKey.toBuffer().copy(data, 16);
+          message.copy(data, 16 + 32);
+          sig.copy(data, 16 + 32 + message.length);
+          return { ixs: [new TransactionInstruction({ programId: Ed25519Program.programId, keys: [], data })], sig };
+        }
+      }
+    ];
+
+    for (const c of cases) {
+      let built = c.build();
+      let tx = new Transaction().add(...built.ixs).add(await resolveIx(built.sig, built.remaining)).add(...(built.after || []));
+      try {
+        await sendAndConfirmTransaction(program.provider.connection, tx, [house]);
+      } catch (e) {
+        assert.equal(anchor.AnchorError.parse(e.logs)?.error.errorCode.code, c.code, c.name);
+        continue;
+      }
+      assert.fail(`expected ${c.code} when ${c.name}`);
+    }
+  });
+
+  it("Rejects a resolution at the bet's expiry and refunds it instead", async () => {
+    let setExpiry = (slots: number) => program.methods.setExpiry(new BN(slots))
+    .accounts({
+      house: house.publicKey,
+      config,
+      emergency
+    })
+    .signers([
+      house
+    ])
+    .rpc().then(confirmTx);
+
+    await setExpiry(SIDE_BET_WINDOW + 1);
+    let stale = new BN(randomBytes(16));
+    let staleBet = PublicKey.findProgramAddressSync([Buffer.from("bet"), vault.toBuffer(), stale.toBuffer("le", 16)], program.programId)[0];
+    await program.methods.placeBet(stale, 50, new BN(LAMPORTS_PER_SOL/100))
+    .accounts({
+      player: player.publicKey,
+      house: house.publicKey,
+      vault,
+      config,
+      bet: staleBet,
+      round: null,
+      nonce: null,
+      claim: null,
+      payoutTable: null,
+      insurance: null,
+      relayer: null,
+      emergency,
+      systemProgram: SystemProgram.programId
+    })
+    .signers([
+      player
+    ])
+    .rpc().then(confirmTx);
+    await setExpiry(0);
+
+    // The expiry was fixed at placement, so clearing it doesn't help
+    let fetched = await program.account.bet.fetch(staleBet, "confirmed");
+    assert.equal(fetched.expiresAtSlot.toNumber(), fetched.slot.toNumber() + SIDE_BET_WINDOW + 1);
+    await waitForSlot(fetched.expiresAtSlot.toNumber());
+    let ix = Ed25519Program.createInstructionWithPrivateKey({ privateKey: house.secretKey, message: betMessage(fetched) });
+    let sig = Buffer.from(ix.data.subarray(16+32, 16+32+64));
+    let rejected = false;
+    try {
+      await sendAndConfirmTransaction(program.provider.connection, new Transaction().add(ix).add(await resolveIx(sig, [], { bet: staleBet })), [house]);
+    } catch (e) {
+      assert.equal(anchor.AnchorError.parse(e.logs)?.error.errorCode.code, "BetExpired");
+      rejected = true;
+    }
+    assert.isTrue(rejected, "expected BetExpired");
+
+    // The same slot opens the refund, so the stake is never stuck
+    let refund = await program.methods.refundBet()
+    .accounts({
+      player: player.publicKey,
+      house: house.publicKey,
+      vault,
+      config,
+      bet: staleBet,
+      round: null,
+      insurance: null,
+      emergency,
+      systemProgram: SystemProgram.programId
+    })
+    .signers([
+      player
+    ])
+    .rpc().then(confirmTx);
+    usage.push(await measure("refundBet", refund));
+    assert.isNull(await anchor.getProvider().connection.getAccountInfo(staleBet, "confirmed"));
+  });
+
+  it("Enables strict signatures", async () => {
+    await program.methods.setStrictSignatures(true)
+    .accounts({
+      house: house.publicKey,
+      config,
+      emergency
+    })
+    .signers([
+      house
+    ])
+    .rpc().then(confirmTx);
+    assert.isTrue((await program.account.config.fetch(config, "confirmed")).strictSignatures);
+  });
+
+  it("Resolve a bet", async () => {
+    // Its own bet, so how long the tests before took can't expire it
+    let fresh = new BN(randomBytes(16));
+    let freshBet = PublicKey.findProgramAddressSync([Buffer.from("bet"), vault.toBuffer(), fresh.toBuffer("le", 16)], program.programId)[0];
+    await program.methods.placeBet(fresh, 50, new BN(LAMPORTS_PER_SOL/100))
+    .accounts({
+      player: player.publicKey,
+      house: house.publicKey,
+      vault,
+      config,
+      bet: freshBet,
+      round: null,
+      nonce: null,
+      claim: null,
+      payoutTable: null,
+      insurance: null,
+      relayer: null,
+      emergency,
+      systemProgram: SystemProgram.programId
+    })
+    .signers([
+      player
+    ])
+    .rpc().then(confirmTx);
+    await waitForSideBets(freshBet);
+
     let sig_ix = Ed25519Program.createInstructionWithPrivateKey({
       privateKey: house.secretKey,
-      message: account.data.subarray(8)
+      message: betMessage(await program.account.bet.fetch(freshBet, "confirmed"))
     });
 
-    const resolve_ix = await program.methods.resolveBet(Buffer.from(sig_ix.data.buffer.slice(16+32, 16+32+64))).accounts({
+    let sig = Buffer.from(sig_ix.data.buffer.slice(16+32, 16+32+64));
+    const resolve_ix = await program.methods.resolveBet(sig).accounts({
         player: player.publicKey,
         house: house.publicKey,
         vault,
-        bet,
+        config,
+        bet: freshBet,
+        round: null,
+        claim: null,
+        memoProgram: null,
+        insurance: null,
+        emergency,
         instructionSysvar: SYSVAR_INSTRUCTIONS_PUBKEY,
         systemProgram:SystemProgram.programId 
       }
@@ -76,20 +827,1168 @@ describe("soldice-anchor", () => {
     ])
     .instruction();
 
-    const tx = new Transaction().add(sig_ix).add(resolve_ix);
+    // Wallets commonly prepend compute budget instructions
+    const tx = new Transaction()
+      .add(ComputeBudgetProgram.setComputeUnitLimit({ units: 400_000 }))
+      .add(sig_ix)
+      .add(resolve_ix);
 
     try {
-      await sendAndConfirmTransaction(
+      let signature = await sendAndConfirmTransaction(
         program.provider.connection,
         tx,
-        [house]
+        [house],
+        { commitment: "confirmed" }
       );
+      usage.push(await measure("resolveBet", signature));
+
+      // Anyone can recompute the result commitment from the event and the house signature
+      let logs = (await anchor.getProvider().connection.getTransaction(signature, { commitment: "confirmed", maxSupportedTransactionVersion: 0 })).meta.logMessages;
+      let resolved = [...new anchor.EventParser(program.programId, program.coder).parseLogs(logs)].find((e) => e.name == "BetResolved").data;
+      let commitment = createHash("sha256").update(Buffer.concat([
+        Buffer.from("soldice:result"),
+        Buffer.from([resolved.gameKind as number]),
+        freshBet.toBuffer(),
+        sig,
+        Buffer.from([resolved.roll as number])
+      ])).digest();
+      assert.isTrue(commitment.equals(Buffer.from(resolved.commitment as number[])));
     } catch (error) {
       console.error(error);
       throw (error)
     }
   });
-});
+
+  it("Ignores the signature argument in strict mode", async () => {
+    let placed = await placeRolling(false, 50);
+    let garbage = randomBytes(64);
+    await waitForSideBets(placed.bet);
+    let signature = await sendAndConfirmTransaction(program.provider.connection, new Transaction().add(placed.ix).add(await resolveIx(garbage, [], { bet: placed.bet })), [house], { commitment: "confirmed" });
+    assert.isNull(await anchor.getProvider().connection.getAccountInfo(placed.bet, "confirmed"));
+
+    // The roll came from the verified signature, not the argument
+    let logs = (await anchor.getProvider().connection.getTransaction(signature, { commitment: "confirmed", maxSupportedTransactionVersion: 0 })).meta.logMessages;
+    let resolved = [...new anchor.EventParser(program.programId, program.coder).parseLogs(logs)].find((e) => e.name == "BetResolved").data;
+    assert.equal(resolved.roll, rollOf(placed.sig));
+  });
+
+  it("Pays out insurance on an insured loss", async () => {
+    let insurance = PublicKey.findProgramAddressSync([Buffer.from("insurance"), house.publicKey.toBuffer()], program.programId)[0];
+    await program.methods.setInsurance(100, 5000)
+    .accounts({
+      house: house.publicKey,
+      config,
+      emergency
+    })
+    .signers([
+      house
+    ])
+    .rpc().then(confirmTx);
+    await program.methods.fundInsurance(new BN(LAMPORTS_PER_SOL))
+    .accounts({
+      house: house.publicKey,
+      config,
+      insurance,
+      systemProgram: SystemProgram.programId
+    })
+    .signers([
+      house
+    ])
+    .rpc().then(confirmTx);
+
+    let insured = await placeRolling(false, 50, { insurance });
+    assert.equal((await program.account.bet.fetch(insured.bet, "confirmed")).premium.toNumber(), LAMPORTS_PER_SOL/100 * 100 / 10_000);
+
+    let connection = anchor.getProvider().connection;
+    let rent = await connection.getBalance(insured.bet, "confirmed");
+    let before = await connection.getBalance(player.publicKey, "confirmed");
+    usage.push(await measure("resolveBetInsured", await sendResolve(insured, [], { insurance })));
+    // Half the stake back, plus the bet account's rent
+    assert.equal(await connection.getBalance(player.publicKey, "confirmed") - before, rent + LAMPORTS_PER_SOL/100 * 5000 / 10_000);
+  });
+
+  it("Settles winning and losing side bets with the bet", async () => {
+    let connection = anchor.getProvider().connection;
+    for (const won of [true, false]) {
+      let placed = await placeRolling(won, 50);
+      // One side bet backing the player to win, and one backing them to lose
+      let sides = [{ bettor, onWin: true }, { bettor: backer, onWin: false }].map((s) => ({
+        ...s,
+        address: PublicKey.findProgramAddressSync([Buffer.from("side"), placed.bet.toBuffer(), s.bettor.publicKey.toBuffer()], program.programId)[0]
+      }));
+      let remaining = sides.reduce((keys, s) => keys.concat([s.address, s.bettor.publicKey]), []);
+
+      if (won) {
+        // A bet with no side bets yet can't be resolved inside the window
+        // either, or a side bet could front-run the resolution
+        let rejected = false;
+        try {
+          await sendAndConfirmTransaction(connection, new Transaction().add(placed.ix).add(await resolveIx(placed.sig, [], { bet: placed.bet })), [house]);
+        } catch (e) {
+          assert.equal(anchor.AnchorError.parse(e.logs)?.error.errorCode.code, "SideBetsOpen");
+          rejected = true;
+        }
+        assert.isTrue(rejected, "expected SideBetsOpen");
+      }
+
+      for (const s of sides) {
+        let placedSide = await program.methods.placeSideBet(new BN(LAMPORTS_PER_SOL/100), s.onWin)
+        .accounts({
+          bettor: s.bettor.publicKey,
+          house: house.publicKey,
+          vault,
+          config,
+          bet: placed.bet,
+          sideBet: s.address,
+          emergency,
+          systemProgram: SystemProgram.programId
+        })
+        .signers([
+          s.bettor
+        ])
+        .rpc().then(confirmTx);
+        usage.push(await measure("placeSideBet", placedSide));
+      }
+
+      let rents = await Promise.all(sides.map((s) => connection.getBalance(s.address, "confirmed")));
+      let before = await Promise.all(sides.map((s) => connection.getBalance(s.bettor.publicKey, "confirmed")));
+      usage.push(await measure("resolveBetSideBets", await sendResolve(placed, remaining)));
+      for (const [i, s] of sides.entries()) {
+        assert.isNull(await connection.getAccountInfo(s.address, "confirmed"));
+        // 1% stakes at roll 50 on the default 1.5% edge, at the odds of the
+        // side backed. A losing side bet only gets its account rent back.
+        let payout = s.onWin == won ? Math.floor(Math.floor(LAMPORTS_PER_SOL/100 * 9850 / (s.onWin ? 49 : 51)) / 100) : 0;
+        assert.equal(await connection.getBalance(s.bettor.publicKey, "confirmed") - before[i], rents[i] + payout, `${s.onWin ? "win" : "loss"} side bet when the player ${won ? "won" : "lost"}`);
+      }
+    }
+  });
+
+  // Places bets until the house signature for one of them rolls the wanted
+  // outcome, so outcome-specific tests don't depend on luck
+  it("Writes the outcome memo through the memo program", async () => {
+    let placed = await placeRolling(true, 50);
+    let rejected = false;
+    try {
+      await sendResolve(placed, [], { memoProgram: SystemProgram.programId });
+    } catch (e) {
+      assert.equal(anchor.AnchorError.parse(e.logs)?.error.errorCode.code, "ConstraintAddress");
+      rejected = true;
+    }
+    assert.isTrue(rejected, "expected ConstraintAddress");
+
+    let signature = await sendResolve(placed, [], { memoProgram: MEMO_PROGRAM_ID });
+    usage.push(await measure("resolveBetMemo", signature));
+    let tx = await anchor.getProvider().connection.getTransaction(signature, { commitment: "confirmed", maxSupportedTransactionVersion: 0 });
+    let keys = tx.transaction.message.getAccountKeys();
+    let memos = tx.meta.innerInstructions
+      .reduce((ixs, inner) => ixs.concat(inner.instructions), [])
+      .filter((ix) => keys.get(ix.programIdIndex).equals(MEMO_PROGRAM_ID))
+      .map((ix) => Buffer.from(anchor.utils.bytes.bs58.decode(ix.data)).toString());
+    let resolved = [...new anchor.EventParser(program.programId, program.coder).parseLogs(tx.meta.logMessages)].find((e) => e.name == "BetResolved").data;
+    assert.deepEqual(memos, [`soldice:v1 bet=${placed.bet.toBase58()} roll=${resolved.roll} payout=${resolved.payout.toString()}`]);
+  });
+
+  const placeRolling = async (won: boolean, roll: number, accounts: { [name: string]: PublicKey | null } = {}): Promise<{ bet: PublicKey, ix: TransactionInstruction, sig: Buffer }> => {
+    for (let i = 0; i < 32; i++) {
+      let seed = new BN(randomBytes(16));
+      let address = PublicKey.findProgramAddressSync([Buffer.from("bet"), vault.toBuffer(), seed.toBuffer("le", 16)], program.programId)[0];
+      await program.methods.placeBet(seed, roll, new BN(LAMPORTS_PER_SOL/100))
+      .accounts({
+        player: player.publicKey,
+        house: house.publicKey,
+        vault,
+        config,
+        bet: address,
+        round: null,
+        nonce: null,
+        claim: null,
+        payoutTable: null,
+        insurance: null,
+        relayer: null,
+        emergency,
+        systemProgram: SystemProgram.programId,
+        ...accounts
+      })
+      .signers([
+        player
+      ])
+      .rpc().then(confirmTx);
+      let ix = Ed25519Program.createInstructionWithPrivateKey({
+        privateKey: house.secretKey,
+        message: betMessage(await program.account.bet.fetch(address, "confirmed"))
+      });
+      let sig = Buffer.from(ix.data.subarray(16+32, 16+32+64));
+      if ((rollOf(sig) < roll) == won) {
+        return { bet: address, ix, sig };
+      }
+    }
+    throw new Error(`no ${won ? "winning" : "losing"} roll found`);
+  }
+
+  // No bet resolves until its side bets have closed
+  const waitForSideBets = async (address: PublicKey) => {
+    let { slot } = await program.account.bet.fetch(address, "confirmed");
+    await waitForSlot(slot.toNumber() + SIDE_BET_WINDOW);
+  }
+
+  // Resolves a bet from placeRolling with the signature found for it
+  const sendResolve = async (placed: { bet: PublicKey, ix: TransactionInstruction, sig: Buffer }, remaining: PublicKey[] = [], accounts: { [name: string]: PublicKey | null } = {}): Promise<string> => {
+    await waitForSideBets(placed.bet);
+    return await sendAndConfirmTransaction(program.provider.connection, new Transaction().add(placed.ix).add(await resolveIx(placed.sig, remaining, { bet: placed.bet, ...accounts })), [house], { commitment: "confirmed" });
+  }
+
+  // Resolves the shared bet unless `accounts` says otherwise
+  const resolveIx = async (sig: Buffer, remaining: PublicKey[] = [], accounts: { [name: string]: PublicKey | null } = {}): Promise<TransactionInstruction> => {
+    return await program.methods.resolveBet(sig).accounts({
+      player: player.publicKey,
+      house: house.publicKey,
+      vault,
+      config,
+      bet,
+      round: null,
+      claim: null,
+      memoProgram: null,
+      insurance: null,
+      emergency,
+      instructionSysvar: SYSVAR_INSTRUCTIONS_PUBKEY,
+      systemProgram: SystemProgram.programId,
+      ...accounts
+    })
+    .remainingAccounts(remaining.map((pubkey) => ({ pubkey, isSigner: false, isWritable: true })))
+    .instruction();
+  }
+
+  it("Rejects a claim with nothing owed", async () => {
+    let claim = PublicKey.findProgramAddressSync([Buffer.from("claim"), vault.toBuffer(), player.publicKey.toBuffer()], program.programId)[0];
+    await program.methods.initClaim()
+    .accounts({
+      player: player.publicKey,
+      house: house.publicKey,
+      vault,
+      config,
+      claim,
+      systemProgram: SystemProgram.programId
+    })
+    .signers([
+      player
+    ])
+    .rpc().then(confirmTx);
+
+    try {
+      await program.methods.claimWinnings()
+      .accounts({
+        player: player.publicKey,
+        house: house.publicKey,
+        vault,
+        config,
+        claim,
+        systemProgram: SystemProgram.programId
+      })
+      .signers([
+        player
+      ])
+      .rpc();
+    } catch (e) {
+      assert.equal(e.error.errorCode.code, "NothingToClaim");
+      return;
+    }
+    assert.fail("expected NothingToClaim");
+  });
+
+  it("Pays a claim-mode win through claim_winnings", async () => {
+    let claim = PublicKey.findProgramAddressSync([Buffer.from("claim"), vault.toBuffer(), player.publicKey.toBuffer()], program.programId)[0];
+    let setClaimPayouts = (claimPayouts: boolean) => program.methods.setClaimPayouts(claimPayouts)
+    .accounts({
+      house: house.publicKey,
+      config,
+      emergency
+    })
+    .signers([
+      house
+    ])
+    .rpc().then(confirmTx);
+
+    await setClaimPayouts(true);
+
+    // Otherwise the player could wait to see the roll before creating it
+    let unclaimed = new BN(randomBytes(16));
+    let rejected = false;
+    try {
+      await program.methods.placeBet(unclaimed, 50, new BN(LAMPORTS_PER_SOL/100))
+      .accounts({
+        player: player.publicKey,
+        house: house.publicKey,
+        vault,
+        config,
+        bet: PublicKey.findProgramAddressSync([Buffer.from("bet"), vault.toBuffer(), unclaimed.toBuffer("le", 16)], program.programId)[0],
+        round: null,
+        nonce: null,
+        claim: null,
+        payoutTable: null,
+        insurance: null,
+        relayer: null,
+        emergency,
+        systemProgram: SystemProgram.programId
+      })
+      .signers([
+        player
+      ])
+      .rpc();
+    } catch (e) {
+      assert.equal(e.error.errorCode.code, "ClaimMismatch");
+      rejected = true;
+    }
+    assert.isTrue(rejected, "expected ClaimMismatch");
+
+    let winning = await placeRolling(true, 50, { claim });
+    // The bet keeps the payout mode it was placed under
+    await setClaimPayouts(false);
+    await sendResolve(winning, [], { claim });
+
+    // 1% stake at roll 50 on the default 1.5% edge
+    let owed = Math.floor(Math.floor(LAMPORTS_PER_SOL/100 * 9850 / 49) / 100);
+    assert.equal((await program.account.claim.fetch(claim, "confirmed")).owed.toNumber(), owed);
+
+    let before = await anchor.getProvider().connection.getBalance(player.publicKey, "confirmed");
+    await program.methods.claimWinnings()
+    .accounts({
+      player: player.publicKey,
+      house: house.publicKey,
+      vault,
+      config,
+      claim,
+      systemProgram: SystemProgram.programId
+    })
+    .signers([
+      player
+    ])
+    .rpc().then(confirmTx);
+    assert.equal(await anchor.getProvider().connection.getBalance(player.publicKey, "confirmed") - before, owed);
+    assert.equal((await program.account.claim.fetch(claim, "confirmed")).owed.toNumber(), 0);
+  });
+
+  it("Reimburses a relayer out of the stake", async () => {
+    let setRelayerSurcharge = (bps: number) => program.methods.setRelayerSurcharge(bps)
+    .accounts({
+      house: house.publicKey,
+      config,
+      emergency
+    })
+    .signers([
+      house
+    ])
+    .rpc().then(confirmTx);
+    await setRelayerSurcharge(100);
+
+    let relayed = new BN(randomBytes(16));
+    let relayedBet = PublicKey.findProgramAddressSync([Buffer.from("bet"), vault.toBuffer(), relayed.toBuffer("le", 16)], program.programId)[0];
+    let tx = await program.methods.placeBet(relayed, 50, new BN(LAMPORTS_PER_SOL/100))
+    .accounts({
+      player: player.publicKey,
+      house: house.publicKey,
+      vault,
+      config,
+      bet: relayedBet,
+      round: null,
+      nonce: null,
+      claim: null,
+      payoutTable: null,
+      insurance: null,
+      relayer: relayer.publicKey,
+      emergency,
+      systemProgram: SystemProgram.programId
+    })
+    .transaction();
+
+    // The relayer pays the fee, the player only signs for the stake
+    let connection = anchor.getProvider().connection;
+    let before = await connection.getBalance(relayer.publicKey, "confirmed");
+    let signature = await sendAndConfirmTransaction(connection, tx, [relayer, player], { commitment: "confirmed" });
+    let { fee } = (await connection.getTransaction(signature, { commitment: "confirmed", maxSupportedTransactionVersion: 0 })).meta;
+    let surcharge = LAMPORTS_PER_SOL/100 * 100 / 10_000;
+    assert.equal(await connection.getBalance(relayer.publicKey, "confirmed") - before, surcharge - fee);
+    assert.equal((await program.account.bet.fetch(relayedBet, "confirmed")).amount.toNumber(), LAMPORTS_PER_SOL/100 - surcharge);
+
+    await setRelayerSurcharge(0);
+  });
+
+  it("Places a bet under a compact seed", async () => {
+    let setCompactSeeds = (compactSeeds: boolean) => program.methods.setCompactSeeds(compactSeeds)
+    .accounts({
+      house: house.publicKey,
+      config,
+      emergency
+    })
+    .signers([
+      house
+    ])
+    .rpc().then(confirmTx);
+    await setCompactSeeds(true);
+
+    // Addressed by the player and a small counter, rather than 16 random bytes
+    let compact = new BN(0);
+    let compactBet = PublicKey.findProgramAddressSync([Buffer.from("bet"), vault.toBuffer(), player.publicKey.toBuffer(), compact.toArrayLike(Buffer, "le", 8)], program.programId)[0];
+    let addresses = await program.methods.deriveAddresses(player.publicKey, compact)
+    .accounts({
+      house: house.publicKey,
+      config
+    })
+    .view();
+    assert.isTrue(addresses.bet.equals(compactBet));
+    await program.methods.placeBet(compact, 50, new BN(LAMPORTS_PER_SOL/100))
+    .accounts({
+      player: player.publicKey,
+      house: house.publicKey,
+      vault,
+      config,
+      bet: compactBet,
+      round: null,
+      nonce: null,
+      claim: null,
+      payoutTable: null,
+      insurance: null,
+      relayer: null,
+      emergency,
+      systemProgram: SystemProgram.programId
+    })
+    .signers([
+      player
+    ])
+    .rpc().then(confirmTx);
+    let fetched = await program.account.bet.fetch(compactBet, "confirmed");
+    assert.isTrue(fetched.compactSeed);
+
+    // It resolves like any other bet
+    let ix = Ed25519Program.createInstructionWithPrivateKey({ privateKey: house.secretKey, message: betMessage(fetched) });
+    let sig = Buffer.from(ix.data.subarray(16+32, 16+32+64));
+    await waitForSideBets(compactBet);
+    await sendAndConfirmTransaction(program.provider.connection, new Transaction().add(ix).add(await resolveIx(sig, [], { bet: compactBet })), [house], { commitment: "confirmed" });
+    assert.isNull(await anchor.getProvider().connection.getAccountInfo(compactBet, "confirmed"));
+
+    await setCompactSeeds(false);
+  });
+
+  it("Sweeps expired bets with refund_bets", async () => {
+    let setExpiry = (slots: number) => program.methods.setExpiry(new BN(slots))
+    .accounts({
+      house: house.publicKey,
+      config,
+      emergency
+    })
+    .signers([
+      house
+    ])
+    .rpc().then(confirmTx);
+    await setExpiry(25);
+
+    // One bet each for two players
+    let placed = await Promise.all([player, bettor].map(async (p) => {
+      let seed = new BN(randomBytes(16));
+      let address = PublicKey.findProgramAddressSync([Buffer.from("bet"), vault.toBuffer(), seed.toBuffer("le", 16)], program.programId)[0];
+      await program.methods.placeBet(seed, 50, new BN(LAMPORTS_PER_SOL/100))
+      .accounts({
+        player: p.publicKey,
+        house: house.publicKey,
+        vault,
+        config,
+        bet: address,
+        round: null,
+        nonce: null,
+        claim: null,
+        payoutTable: null,
+        insurance: null,
+        relayer: null,
+        emergency,
+        systemProgram: SystemProgram.programId
+      })
+      .signers([
+        p
+      ])
+      .rpc().then(confirmTx);
+      return { bet: address, player: p.publicKey };
+    }));
+    let [first, second] = placed;
+
+    let otherVault = PublicKey.findProgramAddressSync([Buffer.from("vault"), otherHouse.publicKey.toBuffer()], program.programId)[0];
+    let otherConfig = PublicKey.findProgramAddressSync([Buffer.from("config"), otherHouse.publicKey.toBuffer()], program.programId)[0];
+    await program.methods.initialize(new BN(LAMPORTS_PER_SOL), new BN(0), Buffer.alloc(0))
+    .accounts({
+      house: otherHouse.publicKey,
+      vault: otherVault,
+      config: otherConfig,
+      systemProgram: SystemProgram.programId
+    })
+    .signers([
+      otherHouse
+    ])
+    .rpc().then(confirmTx);
+
+    let sweep = (owner: PublicKey, pairs: { bet: PublicKey, player: PublicKey }[]) => program.methods.refundBets()
+    .accounts({
+      keeper: program.provider.publicKey,
+      house: owner,
+      vault: PublicKey.findProgramAddressSync([Buffer.from("vault"), owner.toBuffer()], program.programId)[0],
+      config: PublicKey.findProgramAddressSync([Buffer.from("config"), owner.toBuffer()], program.programId)[0],
+      round: null,
+      emergency,
+      systemProgram: SystemProgram.programId
+    })
+    .remainingAccounts(pairs.reduce((metas, { bet, player }) => metas.concat([
+      { pubkey: bet, isSigner: false, isWritable: true },
+      { pubkey: player, isSigner: false, isWritable: true }
+    ]), []))
+    .rpc();
+
+    let rejections: { name: string, code: string, run: () => Promise<string> }[] = [
+      { name: "a bet from another vault", code: "RefundAccounts", run: () => sweep(otherHouse.publicKey, [first]) },
+      { name: "a bet that hasn't expired", code: "TimeoutNotReached", run: () => sweep(house.publicKey, [first]) }
+    ];
+    for (const r of rejections) {
+      try {
+        await r.run();
+      } catch (e) {
+        assert.equal(e.error.errorCode.code, r.code, r.name);
+        continue;
+      }
+      assert.fail(`expected ${r.code} for ${r.name}`);
+    }
+
+    let { slot } = await program.account.bet.fetch(second.bet, "confirmed");
+    await waitForSlot(slot.toNumber() + 25);
+
+    // A repeated pair can't be refunded twice, and fails the whole sweep
+    let rejected = false;
+    try {
+      await sweep(house.publicKey, [first, first]);
+    } catch (e) {
+      assert.equal(e.error.errorCode.code, "AccountNotInitialized");
+      rejected = true;
+    }
+    assert.isTrue(rejected, "expected a repeated pair to be rejected");
+    assert.isNotNull(await anchor.getProvider().connection.getAccountInfo(first.bet, "confirmed"));
+
+    let before = await Promise.all(placed.map((p) => anchor.getProvider().connection.getBalance(p.player, "confirmed")));
+    usage.push(await measure("refundBets", await sweep(house.publicKey, placed).then(confirmTx)));
+    for (const [i, p] of placed.entries()) {
+      assert.isNull(await anchor.getProvider().connection.getAccountInfo(p.bet, "confirmed"));
+      assert.isAbove(await anchor.getProvider().connection.getBalance(p.player, "confirmed"), before[i]);
+    }
+
+    await setExpiry(0);
+  });
+
+  it("Ranks a round once every bet in it has settled", async () => {
+    let roundId = new BN(1);
+    let round = PublicKey.findProgramAddressSync([Buffer.from("round"), house.publicKey.toBuffer(), roundId.toArrayLike(Buffer, "le", 8)], program.programId)[0];
+    let setExpiry = (slots: number) => program.methods.setExpiry(new BN(slots))
+    .accounts({
+      house: house.publicKey,
+      config,
+      emergency
+    })
+    .signers([
+      house
+    ])
+    .rpc().then(confirmTx);
+    let enterRound = (p: PublicKey) => program.methods.enterRound(p)
+    .accounts({
+      house: house.publicKey,
+      config,
+      round,
+      emergency
+    })
+    .signers([
+      house
+    ])
+    .rpc();
+    let finalizeRound = () => program.methods.finalizeRound()
+    .accounts({
+      house: house.publicKey,
+      config,
+      round,
+      emergency
+    })
+    .signers([
+      house
+    ])
+    .rpc();
+    let placeBet = async (p: Keypair, stake: number): Promise<PublicKey> => {
+      let seed = new BN(randomBytes(16));
+      let address = PublicKey.findProgramAddressSync([Buffer.from("bet"), vault.toBuffer(), seed.toBuffer("le", 16)], program.programId)[0];
+      await program.methods.placeBet(seed, 50, new BN(stake))
+      .accounts({
+        player: p.publicKey,
+        house: house.publicKey,
+        vault,
+        config,
+        bet: address,
+        round,
+        nonce: null,
+        claim: null,
+        payoutTable: null,
+        insurance: null,
+        relayer: null,
+        emergency,
+        systemProgram: SystemProgram.programId
+      })
+      .signers([
+        p
+      ])
+      .rpc().then(confirmTx);
+      return address;
+    }
+    let sweep = (roundAccount: PublicKey | null, bet: PublicKey) => program.methods.refundBets()
+    .accounts({
+      keeper: program.provider.publicKey,
+      house: house.publicKey,
+      vault,
+      config,
+      round: roundAccount,
+      emergency,
+      systemProgram: SystemProgram.programId
+    })
+    .remainingAccounts([
+      { pubkey: bet, isSigner: false, isWritable: true },
+      { pubkey: player.publicKey, isSigner: false, isWritable: true }
+    ])
+    .rpc();
+
+    await program.methods.createRound(roundId)
+    .accounts({
+      house: house.publicKey,
+      config,
+      round,
+      systemProgram: SystemProgram.programId,
+      emergency
+    })
+    .signers([
+      house
+    ])
+    .rpc().then(confirmTx);
+    // Distinct stakes, so no two players can tie
+    let entrants = [{ p: backer, stake: 3 * LAMPORTS_PER_SOL/100 }, { p: bettor, stake: 2 * LAMPORTS_PER_SOL/100 }, { p: player, stake: LAMPORTS_PER_SOL/100 }];
+    for (const { p } of entrants) {
+      await enterRound(p.publicKey).then(confirmTx);
+    }
+
+    let rejected = false;
+    try {
+      await placeBet(relayer, LAMPORTS_PER_SOL/100);
+    } catch (e) {
+      assert.equal(e.error.errorCode.code, "NotInRound");
+      rejected = true;
+    }
+    assert.isTrue(rejected, "expected only entered players to bet in the round");
+
+    // A bet the player walks away from
+    await setExpiry(SIDE_BET_WINDOW + 1);
+    let abandoned = await placeBet(player, LAMPORTS_PER_SOL/100);
+    await setExpiry(0);
+
+    let placed = [];
+    for (const entrant of entrants) {
+      placed.push({ ...entrant, bet: await placeBet(entrant.p, entrant.stake) });
+    }
+
+    rejected = false;
+    try {
+      await finalizeRound();
+    } catch (e) {
+      assert.equal(e.error.errorCode.code, "RoundOpenBets");
+      rejected = true;
+    }
+    assert.isTrue(rejected, "expected RoundOpenBets");
+
+    let expected = [];
+    for (const { p, stake, bet } of placed) {
+      let ix = Ed25519Program.createInstructionWithPrivateKey({ privateKey: house.secretKey, message: betMessage(await program.account.bet.fetch(bet, "confirmed")) });
+      let sig = Buffer.from(ix.data.subarray(16+32, 16+32+64));
+      await sendResolve({ bet, ix, sig }, [], { round, player: p.publicKey });
+      // Roll 50 on the default 1.5% edge
+      let won = rollOf(sig) < 50 ? Math.floor(Math.floor(stake * 9850 / 49) / 100) : 0;
+      expected.push({ player: p.publicKey, wagered: stake, won });
+    }
+
+    // The abandoned bet keeps the round open until someone sweeps it
+    await waitForSlot((await program.account.bet.fetch(abandoned, "confirmed")).expiresAtSlot.toNumber());
+    rejected = false;
+    try {
+      await sweep(null, abandoned);
+    } catch (e) {
+      assert.equal(e.error.errorCode.code, "RoundMismatch");
+      rejected = true;
+    }
+    assert.isTrue(rejected, "expected a round bet to need its round");
+    await sweep(round, abandoned).then(confirmTx);
+
+    usage.push(await measure("finalizeRound", await finalizeRound().then(confirmTx)));
+    let finalized = await program.account.round.fetch(round, "confirmed");
+    assert.isTrue(finalized.finalized);
+    assert.equal(finalized.openBets, 0);
+    // Ranked by net winnings, and the refund counts for nothing
+    expected.sort((a, b) => (b.won - b.wagered) - (a.won - a.wagered));
+    assert.equal(finalized.entries.length, expected.length);
+    for (const [i, entry] of finalized.entries.entries()) {
+      assert.isTrue(entry.player.equals(expected[i].player), `rank ${i + 1}`);
+      assert.equal(entry.wagered.toNumber(), expected[i].wagered);
+      assert.equal(entry.won.toNumber(), expected[i].won);
+    }
+
+    rejected = false;
+    try {
+      await enterRound(relayer.publicKey);
+    } catch (e) {
+      assert.equal(e.error.errorCode.code, "RoundFinalized");
+      rejected = true;
+    }
+    assert.isTrue(rejected, "expected RoundFinalized");
+  });
+
+  it("Rejects invalid payout tables", async () => {
+    let tooSteep = fairTable(9900);
+    tooSteep[10] = tooSteep[9] + 1;
+    let atStake = fairTable(9900);
+    atStake[94] = 10_000;
+    let aboveFair = fairTable(9900);
+    aboveFair[0] = 1_000_001;
+
+    for (const [name, multipliers, code] of [["rising", tooSteep, "PayoutCurve"], ["paying back only the stake", atStake, "PayoutMultiplier"], ["above fair odds", aboveFair, "PayoutEdge"]] as [string, number[], string][]) {
+      try {
+        await program.methods.setPayoutTable(multipliers)
+        .accounts({
+          house: house.publicKey,
+          config,
+          payoutTable,
+          systemProgram: SystemProgram.programId,
+          emergency
+        })
+        .signers([
+          house
+        ])
+        .rpc();
+      } catch (e) {
+        assert.equal(e.error.errorCode.code, code, name);
+        continue;
+      }
+      assert.fail(`expected ${code} for a table ${name}`);
+    }
+  });
+
+  it("Prices wins from an open payout table", async () => {
+    let multipliers = fairTable(9900);
+    await program.methods.setPayoutTable(multipliers)
+    .accounts({
+      house: house.publicKey,
+      config,
+      payoutTable,
+      systemProgram: SystemProgram.programId,
+      emergency
+    })
+    .signers([
+      house
+    ])
+    .rpc().then(confirmTx);
+    assert.isTrue((await program.account.config.fetch(config, "confirmed")).payoutTable);
+
+    // While the table is open, every bet must be priced from it
+    let fake = new BN(randomBytes(16));
+    let rejected = false;
+    try {
+      await program.methods.placeBet(fake, 50, new BN(LAMPORTS_PER_SOL/100))
+      .accounts({
+        player: player.publicKey,
+        house: house.publicKey,
+        vault,
+        config,
+        bet: PublicKey.findProgramAddressSync([Buffer.from("bet"), vault.toBuffer(), fake.toBuffer("le", 16)], program.programId)[0],
+        round: null,
+        nonce: null,
+        claim: null,
+        payoutTable: null,
+        insurance: null,
+        relayer: null,
+        emergency,
+        systemProgram: SystemProgram.programId
+      })
+      .signers([
+        player
+      ])
+      .rpc();
+    } catch (e) {
+      assert.equal(e.error.errorCode.code, "PayoutTableMismatch");
+      rejected = true;
+    }
+    assert.isTrue(rejected, "expected PayoutTableMismatch");
+
+    let winning = await placeRolling(true, 50, { payoutTable });
+    assert.equal((await program.account.bet.fetch(winning.bet, "confirmed")).multiplier, multipliers[50 - 2]);
+    let connection = anchor.getProvider().connection;
+    let rent = await connection.getBalance(winning.bet, "confirmed");
+    let before = await connection.getBalance(player.publicKey, "confirmed");
+    await sendResolve(winning);
+    assert.equal(await connection.getBalance(player.publicKey, "confirmed") - before, rent + Math.floor(LAMPORTS_PER_SOL/100 * multipliers[50 - 2] / 10_000));
+
+    await program.methods.closePayoutTable()
+    .accounts({
+      house: house.publicKey,
+      config,
+      payoutTable,
+      emergency
+    })
+    .signers([
+      house
+    ])
+    .rpc().then(confirmTx);
+    assert.isFalse((await program.account.config.fetch(config, "confirmed")).payoutTable);
+    assert.isNull(await connection.getAccountInfo(payoutTable, "confirmed"));
+  });
+
+  it("Closes the table after a loss streak until the cooldown ends", async () => {
+    let setLossLimit = (limit: number, window: number, cooldown: number) => program.methods.setLossLimit(new BN(limit), new BN(window), new BN(cooldown))
+    .accounts({
+      house: house.publicKey,
+      config,
+      emergency
+    })
+    .signers([
+      house
+    ])
+    .rpc().then(confirmTx);
+    let placeBet = () => {
+      let seed = new BN(randomBytes(16));
+      return program.methods.placeBet(seed, 50, new BN(LAMPORTS_PER_SOL/100))
+      .accounts({
+        player: player.publicKey,
+        house: house.publicKey,
+        vault,
+        config,
+        bet: PublicKey.findProgramAddressSync([Buffer.from("bet"), vault.toBuffer(), seed.toBuffer("le", 16)], program.programId)[0],
+        round: null,
+        nonce: null,
+        claim: null,
+        payoutTable: null,
+        insurance: null,
+        relayer: null,
+        emergency,
+        systemProgram: SystemProgram.programId
+      })
+      .signers([
+        player
+      ])
+      .rpc();
+    }
+
+    // Any single win puts the house over a 1 lamport limit
+    await setLossLimit(1, 1000, 10);
+    let winning = await placeRolling(true, 50);
+    await sendResolve(winning);
+
+    let rejected = false;
+    try {
+      await placeBet();
+    } catch (e) {
+      assert.equal(e.error.errorCode.code, "TableClosed");
+      rejected = true;
+    }
+    assert.isTrue(rejected, "expected TableClosed");
+
+    let { pausedUntil } = await program.account.config.fetch(config, "confirmed");
+    await waitForSlot(pausedUntil.toNumber());
+    await placeBet().then(confirmTx);
+
+    await setLossLimit(0, 0, 0);
+  });
+
+  it("Requires each seed to come from the player's nonce in strict mode", async () => {
+    let nonce = PublicKey.findProgramAddressSync([Buffer.from("nonce"), vault.toBuffer(), player.publicKey.toBuffer()], program.programId)[0];
+    let setStrictNonce = (strictNonce: boolean) => program.methods.setStrictNonce(strictNonce)
+    .accounts({
+      house: house.publicKey,
+      config,
+      emergency
+    })
+    .signers([
+      house
+    ])
+    .rpc().then(confirmTx);
+    let placeBet = (seed: BN, nonceAccount: PublicKey | null) => program.methods.placeBet(seed, 50, new BN(LAMPORTS_PER_SOL/100))
+    .accounts({
+      player: player.publicKey,
+      house: house.publicKey,
+      vault,
+      config,
+      bet: PublicKey.findProgramAddressSync([Buffer.from("bet"), vault.toBuffer(), seed.toBuffer("le", 16)], program.programId)[0],
+      round: null,
+      nonce: nonceAccount,
+      claim: null,
+      payoutTable: null,
+      insurance: null,
+      relayer: null,
+      emergency,
+      systemProgram: SystemProgram.programId
+    })
+    .signers([
+      player
+    ])
+    .rpc();
+    // Mirror of PlayerNonce::next_seed for u128 seeds
+    let nextSeed = (n: BN) => new BN(player.publicKey.toBuffer().subarray(0, 8), "le").shln(64).or(n);
+
+    await program.methods.initNonce()
+    .accounts({
+      player: player.publicKey,
+      house: house.publicKey,
+      vault,
+      config,
+      nonce,
+      systemProgram: SystemProgram.programId
+    })
+    .signers([
+      player
+    ])
+    .rpc().then(confirmTx);
+    await setStrictNonce(true);
+
+    let cases: { name: string, code: string, run: () => Promise<string> }[] = [
+      { name: "no nonce account", code: "NonceRequired", run: () => placeBet(nextSeed(new BN(0)), null) },
+      { name: "a seed other than the next nonce", code: "NonceMismatch", run: () => placeBet(nextSeed(new BN(1)), nonce) }
+    ];
+    for (const c of cases) {
+      try {
+        await c.run();
+      } catch (e) {
+        assert.equal(e.error.errorCode.code, c.code, c.name);
+        continue;
+      }
+      assert.fail(`expected ${c.code} for ${c.name}`);
+    }
+
+    await placeBet(nextSeed(new BN(0)), nonce).then(confirmTx);
+    assert.equal((await program.account.playerNonce.fetch(nonce, "confirmed")).nonce.toNumber(), 1);
+    await placeBet(nextSeed(new BN(1)), nonce).then(confirmTx);
+    assert.equal((await program.account.playerNonce.fetch(nonce, "confirmed")).nonce.toNumber(), 2);
+
+    await setStrictNonce(false);
+  });
+
+  it("Keeps refunds open during an emergency", async () => {
+    let stuck = new BN(randomBytes(16));
+    let stuckBet = PublicKey.findProgramAddressSync([Buffer.from("bet"), vault.toBuffer(), stuck.toBuffer("le", 16)], program.programId)[0];
+    let placeBet = (seed: BN, bet: PublicKey) => program.methods.placeBet(seed, 50, new BN(LAMPORTS_PER_SOL/100))
+    .accounts({
+      player: player.publicKey,
+      house: house.publicKey,
+      vault,
+      config,
+      bet,
+      round: null,
+      nonce: null,
+      claim: null,
+      payoutTable: null,
+      insurance: null,
+      relayer: null,
+      emergency,
+      systemProgram: SystemProgram.programId
+    })
+    .signers([
+      player
+    ])
+    .rpc();
+    await placeBet(stuck, stuckBet).then(confirmTx);
+    let sideBet = PublicKey.findProgramAddressSync([Buffer.from("side"), stuckBet.toBuffer(), bettor.publicKey.toBuffer()], program.programId)[0];
+    await program.methods.placeSideBet(new BN(LAMPORTS_PER_SOL/100), false)
+    .accounts({
+      bettor: bettor.publicKey,
+      house: house.publicKey,
+      vault,
+      config,
+      bet: stuckBet,
+      sideBet,
+      emergency,
+      systemProgram: SystemProgram.programId
+    })
+    .signers([
+      bettor
+    ])
+    .rpc().then(confirmTx);
+
+    await program.methods.setEmergency(true)
+    .accounts({
+      authority: program.provider.publicKey,
+      emergency
+    })
+    .rpc().then(confirmTx);
+    // Wait out the timelock
+    let { activeFrom } = await program.account.emergency.fetch(emergency, "confirmed");
+    await waitForSlot(activeFrom.toNumber());
+
+    let rejected = false;
+    try {
+      let fresh = new BN(randomBytes(16));
+      await placeBet(fresh, PublicKey.findProgramAddressSync([Buffer.from("bet"), vault.toBuffer(), fresh.toBuffer("le", 16)], program.programId)[0]);
+    } catch (e) {
+      assert.equal(e.error.errorCode.code, "EmergencyActive");
+      rejected = true;
+    }
+    assert.isTrue(rejected, "expected EmergencyActive");
+
+    // Nor can the house change its terms or run its rounds
+    let round = (id: number) => PublicKey.findProgramAddressSync([Buffer.from("round"), house.publicKey.toBuffer(), new BN(id).toArrayLike(Buffer, "le", 8)], program.programId)[0];
+    let gated: { name: string, run: () => Promise<string> }[] = [
+      { name: "setHouseEdge", run: () => program.methods.setHouseEdge(100).accounts({ house: house.publicKey, config, emergency }).signers([house]).rpc() },
+      { name: "createRound", run: () => program.methods.createRound(new BN(2)).accounts({ house: house.publicKey, config, round: round(2), emergency, systemProgram: SystemProgram.programId }).signers([house]).rpc() },
+      { name: "enterRound", run: () => program.methods.enterRound(bettor.publicKey).accounts({ house: house.publicKey, config, round: round(1), emergency }).signers([house]).rpc() },
+      { name: "finalizeRound", run: () => program.methods.finalizeRound().accounts({ house: house.publicKey, config, round: round(1), emergency }).signers([house]).rpc() }
+    ];
+    for (const g of gated) {
+      try {
+        await g.run();
+      } catch (e) {
+        assert.equal(e.error.errorCode.code, "EmergencyActive", g.name);
+        continue;
+      }
+      assert.fail(`expected EmergencyActive from ${g.name}`);
+    }
+
+    // But it can top up the insurance fund that refunds premiums
+    await program.methods.fundInsurance(new BN(LAMPORTS_PER_SOL/100))
+    .accounts({
+      house: house.publicKey,
+      config,
+      insurance: PublicKey.findProgramAddressSync([Buffer.from("insurance"), house.publicKey.toBuffer()], program.programId)[0],
+      systemProgram: SystemProgram.programId
+    })
+    .signers([
+      house
+    ])
+    .rpc().then(confirmTx);
+
+    // The side bettor doesn't have to wait for the player to refund
+    await program.methods.refundSideBet()
+    .accounts({
+      bettor: bettor.publicKey,
+      house: house.publicKey,
+      vault,
+      config,
+      bet: stuckBet,
+      sideBet,
+      emergency,
+      systemProgram: SystemProgram.programId
+    })
+    .signers([
+      bettor
+    ])
+    .rpc().then(confirmTx);
+    assert.isNull(await anchor.getProvider().connection.getAccountInfo(sideBet, "confirmed"));
+    assert.equal((await program.account.bet.fetch(stuckBet, "confirmed")).sideBets, 0);
+
+    // The bet is well within REFUND_TIMEOUT, but the player can still get out
+    let before = await anchor.getProvider().connection.getBalance(player.publicKey, "confirmed");
+    await program.methods.refundBet()
+    .accounts({
+      player: player.publicKey,
+      house: house.publicKey,
+      vault,
+      config,
+      bet: stuckBet,
+      round: null,
+      insurance: null,
+      emergency,
+      systemProgram: SystemProgram.programId
+    })
+    .signers([
+      player
+    ])
+    .rpc().then(confirmTx);
+    assert.isNull(await anchor.getProvider().connection.getAccountInfo(stuckBet, "confirmed"));
+    assert.isAbove(await anchor.getProvider().connection.getBalance(player.publicKey, "confirmed"), before);
+  });
+
+  it("Stays within compute budgets", async () => {
+    // Compared against a baseline run, when one has been saved
+    let baseline: Usage[] = existsSync(CU_BASELINE) ? JSON.parse(readFileSync(CU_BASELINE, "utf8")) : [];
+    let report = usage.map((u) => {
+      let before = baseline.find((b) => b.name == u.name);
+      return { ...u, delta: before ? u.computeUnits - before.computeUnits : null };
+    });
+    writeFileSync("target/cu-report.json", JSON.stringify(report, null, 2));
+    usage.forEach((u) => {
+      assert.isAtMost(u.computeUnits, CU_BUDGETS[u.name], `${u.name} exceeded its compute budget`);
+    });
+    // So a budget can't silently stop being checked
+    Object.keys(CU_BUDGETS).forEach((name) => {
+      assert.isTrue(usage.some((u) => u.name == name), `${name} was never measured`);
+    });
+  });
+});
+
+type Usage = { name: string, computeUnits: number, fee: number };
+
+// Upper bounds per handler; raise deliberately when a feature needs more
+const CU_BUDGETS: Record<string, number> = {
+  initialize: 30_000,
+  placeBet: 40_000,
+  placeSideBet: 30_000,
+  resolveBet: 80_000,
+  resolveBetSideBets: 120_000,
+  resolveBetMemo: 100_000,
+  resolveBetInsured: 90_000,
+  refundBet: 40_000,
+  refundBets: 60_000,
+  finalizeRound: 40_000
+};
+
+// A target/cu-report.json saved from a run before the change being measured
+const CU_BASELINE = "tests/cu-baseline.json";
+
+const measure = async (name: string, signature: string): Promise<Usage> => {
+  let tx = await anchor.getProvider().connection.getTransaction(signature, { commitment: "confirmed", maxSupportedTransactionVersion: 0 });
+  return {
+    name,
+    computeUnits: tx.meta.computeUnitsConsumed,
+    fee: tx.meta.fee
+  };
+}
+
+const SIDE_BET_WINDOW = 20;
+
+const MEMO_PROGRAM_ID = new PublicKey("MemoSq4gqABAXKb96qnH8TysNcWxMyWCqXgDLGmfcHr");
+
+const BET_MESSAGE_DOMAIN = Buffer.from("soldice:resolve");
+const BET_MESSAGE_VERSION = 1;
+
+const betMessage = (bet: { player: PublicKey, seed: BN, slot: BN, amount: BN, roll: number, bump: number, rngVersion: number, expiresAtSlot: BN }): Buffer => {
+  return Buffer.concat([
+    BET_MESSAGE_DOMAIN,
+    anchor.workspace.SoldiceAnchor.programId.toBuffer(),
+    Buffer.from([BET_MESSAGE_VERSION]),
+    bet.player.toBuffer(),
+    bet.seed.toArrayLike(Buffer, "le", 16),
+    bet.slot.toArrayLike(Buffer, "le", 8),
+    bet.amount.toArrayLike(Buffer, "le", 8),
+    Buffer.from([bet.roll, bet.bump, bet.rngVersion]),
+    bet.expiresAtSlot.toArrayLike(Buffer, "le", 8)
+  ]);
+}
+
+const waitForSlot = async (slot: number) => {
+  while (await anchor.getProvider().connection.getSlot("confirmed") < slot) {
+    await new Promise((resolve) => setTimeout(resolve, 200));
+  }
+}
+
+// Multipliers for rolls 2..=96 returning `rtp` bps of a fair payout
+const fairTable = (rtp: number): number[] => {
+  return Array.from({ length: 95 }, (_, i) => Math.floor(rtp * 100 / (i + 1)));
+}
+
+// Mirror of rng::roll_v1
+const rollOf = (sig: Buffer): number => {
+  let hash = createHash("sha256").update(sig).digest();
+  let lower = new BN(hash.subarray(0, 16), "le");
+  let upper = new BN(hash.subarray(16, 32), "le");
+  return lower.add(upper).maskn(128).modn(100) + 1;
+}
 
 const confirmTx = async (signature: string): Promise<string> => {
   const latestBlockhash = await anchor.getProvider().connection.getLatestBlockhash();